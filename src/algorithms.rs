@@ -26,9 +26,10 @@ pub fn generate_brute_force_hints(grid: &mut Grid) -> Vec<GridLoc> {
                 hints.extend(&neighboring_hidden);
             }
 
-            // If the number of hidden cells equals the remaining mines, all hidden cells are mines
-            let remaining_mines = neighboring_mines - neighboring_flags;
-            if neighboring_hidden.len() as u8 == remaining_mines && remaining_mines > 0 {
+            // If the hidden cells must all be full of mines, they are all mines
+            let remaining_mines = neighboring_mines.saturating_sub(neighboring_flags);
+            let room = neighboring_hidden.len() as u8 * grid.mines_per_cell();
+            if room == remaining_mines && remaining_mines > 0 {
                 hints.extend(neighboring_hidden);
             }
 
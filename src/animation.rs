@@ -0,0 +1,194 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    cell::CellType,
+    grid::{Grid, GridLoc},
+};
+
+/// Time between one ring of cells and the next.
+const STEP: Duration = Duration::from_millis(30);
+/// Longest an effect takes to reach its last cell, however large the board.
+const MAX_SPREAD: Duration = Duration::from_millis(1200);
+/// Time a cell takes to fade in once its turn comes, or to pass under the sweep.
+const FADE: Duration = Duration::from_millis(150);
+/// Opacity cells fade in from, matching hint highlights.
+const DIM: f32 = 0.6;
+
+/// Gameplay effects played on the board after a move.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Effect {
+    /// Cells opened by a cascade appear ring by ring from the clicked cell.
+    Ripple,
+    /// On a loss, the board is uncovered outward from the mine that went off.
+    Explosion,
+    /// On a win, a wave of light sweeps across the board from its top left corner.
+    Sweep,
+}
+
+/// How an effect changes a cell at some moment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CellEffect {
+    /// The cell still looks hidden until its turn comes.
+    Covered,
+    /// The cell is drawn at this opacity.
+    Faded(f32),
+}
+
+/// An effect playing on the board, with the moment each cell's turn comes.
+#[derive(Clone, Debug)]
+pub struct BoardAnimation {
+    effect: Effect,
+    started: Instant,
+    delays: HashMap<GridLoc, Duration>,
+    end: Duration,
+}
+
+/// Returns whether each cell is revealed, row by row, to compare boards before and after a move.
+pub fn revealed(grid: &Grid) -> Vec<bool> {
+    grid.locs()
+        .map(|loc| {
+            matches!(
+                grid.get(loc.row, loc.col).unwrap().cell_type,
+                CellType::Revealed
+            )
+        })
+        .collect()
+}
+
+impl BoardAnimation {
+    /// Plans `effect` for a move at `origin`, given which cells were revealed
+    /// before it, or `None` if the board was just created.
+    ///
+    /// Returns `None` when the move uncovered nothing worth animating.
+    pub fn new(
+        effect: Effect,
+        grid: &Grid,
+        before: Option<&[bool]>,
+        origin: GridLoc,
+        now: Instant,
+    ) -> Option<Self> {
+        let uncovered: Vec<GridLoc> = grid
+            .locs()
+            .zip(revealed(grid))
+            .enumerate()
+            .filter(|&(index, (_, revealed))| {
+                revealed && !before.is_some_and(|before| before[index])
+            })
+            .map(|(_, (loc, _))| loc)
+            .collect();
+        let rings: HashMap<GridLoc, u32> = match effect {
+            Effect::Ripple => rings_through(grid, origin, &uncovered),
+            Effect::Explosion => {
+                let distance = |loc: GridLoc| {
+                    let rows = loc.row.abs_diff(origin.row) as f32;
+                    let cols = loc.col.abs_diff(origin.col) as f32;
+                    rows.hypot(cols).round() as u32
+                };
+                uncovered.iter().map(|&loc| (loc, distance(loc))).collect()
+            }
+            Effect::Sweep => grid
+                .locs()
+                .map(|loc| (loc, (loc.row + loc.col) as u32))
+                .collect(),
+        };
+        let last = rings.values().copied().max()?;
+        if effect == Effect::Ripple && last == 0 {
+            return None;
+        }
+        let step = STEP.min(MAX_SPREAD / last.max(1));
+        Some(Self {
+            effect,
+            started: now,
+            delays: rings
+                .into_iter()
+                .map(|(loc, ring)| (loc, step * ring))
+                .collect(),
+            end: step * last + FADE,
+        })
+    }
+
+    /// Returns whether the effect is still playing.
+    pub fn is_running(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.started) < self.end
+    }
+
+    /// Returns how the effect changes a cell at `now`, or `None` if it leaves the cell alone.
+    pub fn cell(&self, loc: GridLoc, now: Instant) -> Option<CellEffect> {
+        let delay = *self.delays.get(&loc)?;
+        let elapsed = now
+            .saturating_duration_since(self.started)
+            .checked_sub(delay);
+        let progress = |elapsed: Duration| (elapsed.as_secs_f32() / FADE.as_secs_f32()).min(1.0);
+        match (self.effect, elapsed) {
+            (Effect::Ripple | Effect::Explosion, None) => Some(CellEffect::Covered),
+            (Effect::Ripple | Effect::Explosion, Some(elapsed)) if elapsed < FADE => {
+                Some(CellEffect::Faded(DIM + (1.0 - DIM) * progress(elapsed)))
+            }
+            // The sweep dims each cell and brings it back as the wave passes.
+            (Effect::Sweep, Some(elapsed)) if elapsed < FADE => {
+                let wave = 1.0 - (2.0 * progress(elapsed) - 1.0).abs();
+                Some(CellEffect::Faded(1.0 - (1.0 - DIM) * wave))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Numbers the uncovered cells by how many steps away from `origin` they are,
+/// stepping only through uncovered cells, as a cascade spreads.
+fn rings_through(grid: &Grid, origin: GridLoc, uncovered: &[GridLoc]) -> HashMap<GridLoc, u32> {
+    let mut rings: HashMap<GridLoc, Option<u32>> =
+        uncovered.iter().map(|&loc| (loc, None)).collect();
+    let mut queue = VecDeque::from([(origin, 0)]);
+    if let Some(ring) = rings.get_mut(&origin) {
+        *ring = Some(0);
+    }
+    while let Some((loc, ring)) = queue.pop_front() {
+        for neighbor in grid.neighbors(loc) {
+            if let Some(next @ None) = rings.get_mut(&neighbor) {
+                *next = Some(ring + 1);
+                queue.push_back((neighbor, ring + 1));
+            }
+        }
+    }
+    rings
+        .into_iter()
+        .filter_map(|(loc, ring)| Some((loc, ring?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{grid::GridSize, topology::Topology};
+
+    #[test]
+    fn test_ripple_order() {
+        let mut grid = Grid::new(GridSize { rows: 9, cols: 9 }, Topology::Square);
+        for (row, col) in [(0, 1), (1, 0), (8, 8)] {
+            grid.get_mut(row, col).unwrap().mines = 1;
+        }
+        let origin = GridLoc { row: 4, col: 4 };
+        grid.cascade_reveal(origin);
+        let now = Instant::now();
+        let ripple = BoardAnimation::new(Effect::Ripple, &grid, None, origin, now).unwrap();
+
+        // The clicked cell shows first and the rest of the opening follows it.
+        assert_eq!(ripple.cell(origin, now), Some(CellEffect::Faded(DIM)));
+        let later = grid
+            .neighbors(origin)
+            .find(|&loc| ripple.delays.contains_key(&loc))
+            .unwrap();
+        assert_eq!(ripple.cell(later, now), Some(CellEffect::Covered));
+        assert_eq!(ripple.cell(later, now + STEP + FADE), None);
+        assert!(ripple.is_running(now));
+        assert!(!ripple.is_running(now + MAX_SPREAD + FADE));
+
+        // A move that uncovers nothing has nothing to animate.
+        let after = revealed(&grid);
+        assert!(BoardAnimation::new(Effect::Ripple, &grid, Some(&after), origin, now).is_none());
+    }
+}
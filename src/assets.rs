@@ -1,21 +1,42 @@
-use std::sync::LazyLock;
-
 use iced::widget::{Image, image};
 
-const CLOCK_BYTES: [&[u8]; 10] = [
-    include_bytes!("../assets/c0.png"),
-    include_bytes!("../assets/c1.png"),
-    include_bytes!("../assets/c2.png"),
-    include_bytes!("../assets/c3.png"),
-    include_bytes!("../assets/c4.png"),
-    include_bytes!("../assets/c5.png"),
-    include_bytes!("../assets/c6.png"),
-    include_bytes!("../assets/c7.png"),
-    include_bytes!("../assets/c8.png"),
-    include_bytes!("../assets/c9.png"),
+use crate::skin::Skin;
+
+/// File names of the tiles every skin provides, in the order of `Skin::tiles`.
+pub const TILE_NAMES: [&str; 29] = [
+    "0.png",
+    "1.png",
+    "2.png",
+    "3.png",
+    "4.png",
+    "5.png",
+    "6.png",
+    "7.png",
+    "8.png",
+    "c0.png",
+    "c1.png",
+    "c2.png",
+    "c3.png",
+    "c4.png",
+    "c5.png",
+    "c6.png",
+    "c7.png",
+    "c8.png",
+    "c9.png",
+    "cminus.png",
+    "tile.png",
+    "mine.png",
+    "flag.png",
+    "mineactive.png",
+    "wrongmine.png",
+    "happy.png",
+    "surprised.png",
+    "dead.png",
+    "cool.png",
 ];
 
-const CELL_BYTES: [&[u8]; 9] = [
+/// The tiles of the built-in skin, in the order of `TILE_NAMES`.
+pub const BUILTIN_TILES: [&[u8]; 29] = [
     include_bytes!("../assets/0.png"),
     include_bytes!("../assets/1.png"),
     include_bytes!("../assets/2.png"),
@@ -25,58 +46,87 @@ const CELL_BYTES: [&[u8]; 9] = [
     include_bytes!("../assets/6.png"),
     include_bytes!("../assets/7.png"),
     include_bytes!("../assets/8.png"),
-];
-
-const FACE_BYTES: [&[u8]; 2] = [
+    include_bytes!("../assets/c0.png"),
+    include_bytes!("../assets/c1.png"),
+    include_bytes!("../assets/c2.png"),
+    include_bytes!("../assets/c3.png"),
+    include_bytes!("../assets/c4.png"),
+    include_bytes!("../assets/c5.png"),
+    include_bytes!("../assets/c6.png"),
+    include_bytes!("../assets/c7.png"),
+    include_bytes!("../assets/c8.png"),
+    include_bytes!("../assets/c9.png"),
+    include_bytes!("../assets/cminus.png"),
+    include_bytes!("../assets/tile.png"),
+    include_bytes!("../assets/mine.png"),
+    include_bytes!("../assets/flag.png"),
+    include_bytes!("../assets/mineactive.png"),
+    include_bytes!("../assets/wrongmine.png"),
     include_bytes!("../assets/happy.png"),
-    include_bytes!("../assets/surpised.png"),
+    include_bytes!("../assets/surprised.png"),
+    include_bytes!("../assets/dead.png"),
+    include_bytes!("../assets/cool.png"),
 ];
 
-// const IMAGE_SCALE: f32 = 4.0;
-
-fn create_handle(bytes: &'static [u8]) -> image::Handle {
-    image::Handle::from_bytes(bytes)
-}
+const CELL: usize = 0;
+const CLOCK: usize = 9;
+const MINUS: usize = 19;
+const UNREVEALED_CELL: usize = 20;
+const MINE: usize = 21;
+const FLAG: usize = 22;
+const MINE_ACTIVE: usize = 23;
+const WRONG_FLAG: usize = 24;
+const FACE: usize = 25;
 
-fn create_image(handle: &image::Handle) -> Image {
-    image(handle).filter_method(image::FilterMethod::Nearest)
-}
-
-static MINUS_HANDLE: LazyLock<image::Handle> =
-    LazyLock::new(|| create_handle(include_bytes!("../assets/cminus.png")));
-static CLOCK_HANDLES: LazyLock<[image::Handle; 10]> =
-    LazyLock::new(|| CLOCK_BYTES.map(create_handle));
-static CELL_HANDLES: LazyLock<[image::Handle; 9]> = LazyLock::new(|| CELL_BYTES.map(create_handle));
-static UNREVEALED_CELL_HANDLE: LazyLock<image::Handle> =
-    LazyLock::new(|| create_handle(include_bytes!("../assets/tile.png")));
-static MINE_HANDLE: LazyLock<image::Handle> =
-    LazyLock::new(|| create_handle(include_bytes!("../assets/mine.png")));
-static FLAG_HANDLE: LazyLock<image::Handle> =
-    LazyLock::new(|| create_handle(include_bytes!("../assets/flag.png")));
-static FACE_HANDLES: LazyLock<[image::Handle; 2]> = LazyLock::new(|| FACE_BYTES.map(create_handle));
+// const IMAGE_SCALE: f32 = 4.0;
 
-pub fn get_clock_image(number: usize) -> Image {
-    create_image(&CLOCK_HANDLES[number]).height(40).width(23)
+/// Returns the tile at `index` in `TILE_NAMES` from `skin`.
+fn create_image(skin: &Skin, index: usize) -> Image {
+    image(&skin.tiles[index]).filter_method(image::FilterMethod::Nearest)
 }
 
-pub fn get_cell_image(number: usize) -> Image {
-    create_image(&CELL_HANDLES[number])
+/// A tile showing a cell of the board.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Sprite {
+    /// A revealed cell with this many neighboring mines, up to 8.
+    Number(u8),
+    Unrevealed,
+    Mine,
+    Flag,
+    /// The mine that was revealed, losing the game.
+    MineActive,
+    /// A flag on a cell without as many mines, shown once the game is lost.
+    WrongFlag,
 }
 
-pub fn get_unrevealed_cell_image() -> Image {
-    create_image(&UNREVEALED_CELL_HANDLE)
+impl Sprite {
+    fn index(self) -> usize {
+        match self {
+            Sprite::Number(number) => CELL + number as usize,
+            Sprite::Unrevealed => UNREVEALED_CELL,
+            Sprite::Mine => MINE,
+            Sprite::Flag => FLAG,
+            Sprite::MineActive => MINE_ACTIVE,
+            Sprite::WrongFlag => WRONG_FLAG,
+        }
+    }
+
+    /// Returns the sprite's tile in `skin`.
+    pub fn handle(self, skin: &Skin) -> &image::Handle {
+        &skin.tiles[self.index()]
+    }
 }
 
-pub fn get_mine_image() -> Image {
-    create_image(&MINE_HANDLE)
+pub fn get_clock_image(skin: &Skin, number: usize) -> Image {
+    create_image(skin, CLOCK + number).height(40).width(23)
 }
 
-pub fn get_flag_image() -> Image {
-    create_image(&FLAG_HANDLE).height(40).width(40)
+pub fn get_flag_image(skin: &Skin) -> Image {
+    create_image(skin, FLAG).height(40).width(40)
 }
 
-pub fn get_minus_image() -> Image {
-    create_image(&MINUS_HANDLE)
+pub fn get_minus_image(skin: &Skin) -> Image {
+    create_image(skin, MINUS)
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -84,10 +134,14 @@ pub enum Face {
     #[default]
     Happy = 0,
     Surprised = 1,
+    /// Shown once the game is lost.
+    Dead = 2,
+    /// Shown once the game is won.
+    Cool = 3,
 }
 
-pub fn get_face_image(face: Face) -> Image {
-    create_image(&FACE_HANDLES[face as usize])
+pub fn get_face_image(skin: &Skin, face: Face) -> Image {
+    create_image(skin, FACE + face as usize)
         .height(40)
         .width(40)
 }
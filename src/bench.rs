@@ -0,0 +1,200 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::{
+    grid::{GridConfig, GridLoc},
+    rating::Rating,
+    share::ShareCode,
+    solver,
+};
+
+/// Which boards to generate and how, for `run`.
+#[derive(Clone, Debug)]
+pub struct BenchOptions {
+    pub config: GridConfig,
+    /// Seed of the first board; the others use the following seeds.
+    pub first_seed: u64,
+    pub boards: u64,
+    pub opening: bool,
+    pub no_guess: bool,
+    pub rating: Option<Rating>,
+}
+
+impl BenchOptions {
+    /// Checks that the seeds of every board fit in a `u64`.
+    pub fn validate(&self) -> Result<(), String> {
+        match self.first_seed.checked_add(self.boards.saturating_sub(1)) {
+            Some(..) => Ok(()),
+            None => Err(format!(
+                "{} boards starting at seed {} run past the last seed",
+                self.boards, self.first_seed
+            )),
+        }
+    }
+}
+
+/// Aggregated results of playing every board with the solver.
+#[derive(Clone, Debug, Serialize)]
+pub struct BenchReport {
+    pub rows: usize,
+    pub cols: usize,
+    pub mines: usize,
+    pub first_seed: u64,
+    pub boards: u64,
+    /// Boards no board passing the filters was found for, which are left out of the rest.
+    pub unmatched: u64,
+    pub wins: u64,
+    pub win_rate: f64,
+    /// Boards the solver cleared without ever having to guess.
+    pub guess_free: u64,
+    pub average_guesses: f64,
+    pub average_3bv: f64,
+    pub average_generate_ms: f64,
+    pub average_solve_ms: f64,
+}
+
+/// Generates every board, plays it with the guessing solver from the center and
+/// collects the results.
+pub fn run(options: &BenchOptions) -> BenchReport {
+    let GridConfig { size, mines, .. } = options.config;
+    let start = GridLoc {
+        row: size.rows / 2,
+        col: size.cols / 2,
+    };
+
+    let (mut wins, mut guess_free, mut guesses, mut three_bv) = (0, 0, 0, 0);
+    let mut unmatched = 0;
+    let (mut generating, mut solving) = (Duration::ZERO, Duration::ZERO);
+    let seeds = (0..options.boards).map_while(|offset| options.first_seed.checked_add(offset));
+    for seed in seeds {
+        let code = ShareCode {
+            config: options.config.clone(),
+            start,
+            seed,
+            opening: options.opening,
+            no_guess: options.no_guess,
+            rating: options.rating,
+            layout: None,
+        };
+
+        let started = Instant::now();
+        let generated = code.generate();
+        generating += started.elapsed();
+        let Ok(mut grid) = generated else {
+            unmatched += 1;
+            continue;
+        };
+        three_bv += grid.three_bv();
+
+        let started = Instant::now();
+        let report = solver::solve(&mut grid, true);
+        solving += started.elapsed();
+
+        wins += u64::from(report.solved);
+        guess_free += u64::from(report.solved && report.guesses == 0);
+        guesses += report.guesses;
+    }
+
+    let generated = options.boards.max(1) as f64;
+    let boards = (options.boards - unmatched).max(1) as f64;
+    BenchReport {
+        rows: size.rows,
+        cols: size.cols,
+        mines,
+        first_seed: options.first_seed,
+        boards: options.boards,
+        unmatched,
+        wins,
+        win_rate: wins as f64 / boards,
+        guess_free,
+        average_guesses: guesses as f64 / boards,
+        average_3bv: three_bv as f64 / boards,
+        average_generate_ms: generating.as_secs_f64() * 1000.0 / generated,
+        average_solve_ms: solving.as_secs_f64() * 1000.0 / boards,
+    }
+}
+
+impl BenchReport {
+    /// Formats the report as a two-column table for the terminal.
+    pub fn table(&self) -> String {
+        let last_seed = self
+            .first_seed
+            .saturating_add(self.boards.saturating_sub(1));
+        let rows = [
+            (
+                "Board",
+                format!("{}x{}, {} mines", self.rows, self.cols, self.mines),
+            ),
+            (
+                "Boards",
+                format!(
+                    "{} (seeds {}..={})",
+                    self.boards, self.first_seed, last_seed
+                ),
+            ),
+            ("Unmatched", self.unmatched.to_string()),
+            (
+                "Win rate",
+                format!("{:.1}% ({} wins)", self.win_rate * 100.0, self.wins),
+            ),
+            ("Guess-free", self.guess_free.to_string()),
+            ("Guesses", format!("{:.2} per board", self.average_guesses)),
+            ("3BV", format!("{:.1} per board", self.average_3bv)),
+            (
+                "Generation",
+                format!("{:.3} ms per board", self.average_generate_ms),
+            ),
+            (
+                "Solving",
+                format!("{:.3} ms per board", self.average_solve_ms),
+            ),
+        ];
+        rows.iter()
+            .map(|(name, value)| format!("{name:<12}{value}\n"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        grid::GridSize,
+        topology::{Neighborhood, Topology},
+    };
+
+    #[test]
+    fn test_bench_is_reproducible() {
+        let options = BenchOptions {
+            config: GridConfig {
+                size: GridSize { rows: 9, cols: 9 },
+                mines: 10,
+                topology: Topology::Square,
+                wrap: false,
+                neighborhood: Neighborhood::Standard,
+                mines_per_cell: 1,
+            },
+            first_seed: 0,
+            boards: 20,
+            opening: true,
+            no_guess: false,
+            rating: None,
+        };
+        let (first, second) = (run(&options), run(&options));
+        assert_eq!(first.wins, second.wins);
+        assert_eq!(first.average_3bv, second.average_3bv);
+        assert!(first.wins > 0);
+
+        let last = BenchOptions {
+            first_seed: u64::MAX - 19,
+            ..options.clone()
+        };
+        assert!(last.validate().is_ok());
+        let past_last = BenchOptions {
+            first_seed: u64::MAX - 18,
+            ..options
+        };
+        assert!(past_last.validate().is_err());
+    }
+}
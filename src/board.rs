@@ -0,0 +1,493 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+    time::Instant,
+};
+
+use iced::{
+    Color, Pixels, Point, Rectangle, Renderer, Size, Theme,
+    alignment::Vertical,
+    mouse,
+    widget::{
+        canvas::{self, Action, Cache, Event, Frame, Geometry, Program, Stroke},
+        image::FilterMethod,
+        text::Alignment,
+    },
+};
+
+use crate::{
+    animation::{BoardAnimation, CellEffect},
+    assets::Sprite,
+    cell::{CellType, Label},
+    endless::EndlessBoard,
+    game::ClickMode,
+    grid::{Grid, GridLoc, GridSize},
+    message::Message,
+    settings::ChordStyle,
+    skin::Skin,
+    theme::player_color,
+    topology::Topology,
+};
+
+/// Rows and columns of cells in each block of the board that is cached as a whole.
+const REGION: usize = 16;
+
+/// The cells a board shows.
+#[derive(Clone, Copy, Debug)]
+pub enum BoardCells<'a> {
+    /// Every cell is hidden, before the first click.
+    Hidden,
+    Grid(&'a Grid),
+    /// The part of an endless board under its viewport.
+    Endless(&'a EndlessBoard),
+}
+
+impl BoardCells<'_> {
+    /// Returns a number that changes whenever the cells may have changed.
+    fn revision(self) -> u64 {
+        match self {
+            BoardCells::Hidden => 0,
+            BoardCells::Grid(grid) => grid.revision(),
+            BoardCells::Endless(board) => board.revision(),
+        }
+    }
+
+    /// Returns the state of a cell. Cells of an endless board that were never
+    /// generated are hidden.
+    fn cell_type(self, loc: GridLoc) -> Option<CellType> {
+        match self {
+            BoardCells::Hidden => None,
+            BoardCells::Grid(grid) => grid.get(loc.row, loc.col).map(|cell| cell.cell_type),
+            BoardCells::Endless(board) => Some(
+                board
+                    .get(board.world(loc))
+                    .map_or(CellType::Hidden, |cell| cell.cell_type),
+            ),
+        }
+    }
+}
+
+/// What pressing the cells of a board does.
+#[derive(Clone, Copy, Debug)]
+pub enum BoardInput {
+    /// The board ignores the pointer, as once the game is over.
+    Disabled,
+    /// Either button reveals the cell, placing the mines around it.
+    FirstClick,
+    /// The buttons reveal, chord and flag cells as the click mode says.
+    Play(ClickMode),
+    /// Left clicks pick the cells the player starts with and right clicks add
+    /// mines, as in the board editor.
+    Edit,
+}
+
+/// Draws a whole board from the skin's sprites and turns clicks into moves.
+///
+/// The board is split into blocks of `REGION` by `REGION` cells whose drawings
+/// are cached, so only the blocks whose cells changed are drawn again. Blocks
+/// are only compared cell by cell after the board's revision changes or while
+/// an effect plays, which keeps boards of tens of thousands of cells cheap to show.
+pub struct BoardCanvas<'a> {
+    pub cells: BoardCells<'a>,
+    pub topology: Topology,
+    pub size: GridSize,
+    /// Side of a cell in pixels.
+    pub side: f32,
+    pub now: Instant,
+    pub input: BoardInput,
+    pub chord_style: ChordStyle,
+    /// Whether to report the cell under the pointer, as co-op games do.
+    pub track_pointer: bool,
+    /// Cells other players are pointing at, by player.
+    pub cursors: Vec<(GridLoc, u8)>,
+    /// The effect playing after the last move.
+    pub animation: Option<&'a BoardAnimation>,
+    pub skin: &'a Arc<Skin>,
+}
+
+/// Drawings of the board's blocks and the pointer's last reported cell.
+#[derive(Default)]
+pub struct BoardState {
+    /// Cached drawing of each block, with a fingerprint of what it shows.
+    regions: RefCell<HashMap<(usize, usize), (u64, Cache)>>,
+    /// Hash of the board's revision and look when the blocks were last compared.
+    drawn: Cell<Option<u64>>,
+    pointer: Option<GridLoc>,
+    /// Mouse buttons held down, to chord when both are.
+    left_held: bool,
+    right_held: bool,
+}
+
+impl BoardCanvas<'_> {
+    /// Returns the size of the whole board in pixels.
+    pub fn bounds(&self) -> Size {
+        let width = match self.topology {
+            Topology::Square => self.size.cols as f32,
+            Topology::Hex => self.size.cols as f32 + 0.5,
+        };
+        Size::new(width * self.side, self.size.rows as f32 * self.side)
+    }
+
+    /// Returns the top left corner of a cell. Odd rows of hexagonal boards are
+    /// shifted right by half a cell.
+    fn origin(&self, loc: GridLoc) -> Point {
+        let offset = match self.topology {
+            Topology::Hex if loc.row % 2 == 1 => self.side / 2.0,
+            _ => 0.0,
+        };
+        Point::new(
+            loc.col as f32 * self.side + offset,
+            loc.row as f32 * self.side,
+        )
+    }
+
+    /// Returns the cell under a point relative to the board's top left corner.
+    pub fn loc_at(&self, point: Point) -> Option<GridLoc> {
+        if point.y < 0.0 {
+            return None;
+        }
+        let row = (point.y / self.side) as usize;
+        let x = point.x - self.origin(GridLoc { row, col: 0 }).x;
+        if x < 0.0 {
+            return None;
+        }
+        let col = (x / self.side) as usize;
+        (row < self.size.rows && col < self.size.cols).then_some(GridLoc { row, col })
+    }
+
+    /// Returns the cells of a block.
+    fn region_locs(&self, (row, col): (usize, usize)) -> impl Iterator<Item = GridLoc> + use<> {
+        let rows = row * REGION..((row + 1) * REGION).min(self.size.rows);
+        let cols = col * REGION..((col + 1) * REGION).min(self.size.cols);
+        rows.flat_map(move |row| cols.clone().map(move |col| GridLoc { row, col }))
+    }
+
+    /// Returns the area a block covers, including the half cell odd hexagonal rows overhang.
+    fn region_bounds(&self, (row, col): (usize, usize)) -> Rectangle {
+        let overhang = match self.topology {
+            Topology::Square => 0.0,
+            Topology::Hex => 0.5,
+        };
+        Rectangle::new(
+            Point::new(
+                (col * REGION) as f32 * self.side,
+                (row * REGION) as f32 * self.side,
+            ),
+            Size::new(REGION as f32 + overhang, REGION as f32) * self.side,
+        )
+    }
+
+    /// Returns what a cell shows: its sprite, any number over it, its opacity
+    /// and the player owning its flag.
+    fn appearance(&self, loc: GridLoc) -> (Sprite, Option<Label>, f32, Option<u8>) {
+        let grid = match self.cells {
+            BoardCells::Hidden => return (Sprite::Unrevealed, None, 1.0, None),
+            BoardCells::Grid(grid) => grid,
+            BoardCells::Endless(board) => {
+                let world = board.world(loc);
+                let Some(cell) = board.get(world) else {
+                    return (Sprite::Unrevealed, None, 1.0, None);
+                };
+                let neighboring_mines = match cell.cell_type {
+                    CellType::Revealed => board.count_neighboring_mines(world),
+                    _ => 0,
+                };
+                let (sprite, label) = cell.sprite(neighboring_mines);
+                return (sprite, label, 1.0, None);
+            }
+        };
+        let cell = grid.get(loc.row, loc.col).unwrap();
+        let neighboring_mines = match cell.cell_type {
+            CellType::Revealed => grid.count_neighboring_mines(loc),
+            _ => 0,
+        };
+        let (sprite, label) = match cell.sprite(neighboring_mines) {
+            (Sprite::Unrevealed, None) if grid.is_questioned(loc) => {
+                (Sprite::Unrevealed, Some(Label::Question))
+            }
+            // Lost boards point out the mine that went off and the misplaced flags.
+            (Sprite::Mine, label) if grid.exploded(loc) => (Sprite::MineActive, label),
+            (Sprite::Flag, label)
+                if grid.is_lost() && cell.cell_type != CellType::Flagged(cell.mines) =>
+            {
+                (Sprite::WrongFlag, label)
+            }
+            appearance => appearance,
+        };
+        let opacity = cell.highlight.interpolate(1.0, 0.6, self.now);
+        match self
+            .animation
+            .and_then(|animation| animation.cell(loc, self.now))
+        {
+            Some(CellEffect::Covered) => (Sprite::Unrevealed, None, 1.0, None),
+            Some(CellEffect::Faded(faded)) => {
+                (sprite, label, opacity.min(faded), grid.flag_owner(loc))
+            }
+            None => (sprite, label, opacity, grid.flag_owner(loc)),
+        }
+    }
+
+    /// Hashes everything a block shows, so its drawing is only redone when the hash changes.
+    fn fingerprint(&self, region: (usize, usize), skin: &Arc<Skin>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (Arc::as_ptr(skin) as usize, self.side.to_bits()).hash(&mut hasher);
+        for loc in self.region_locs(region) {
+            let (sprite, label, opacity, owner) = self.appearance(loc);
+            // Highlights fade in steps too small to see, so they are hashed coarsely.
+            (sprite, label, (opacity * 64.0) as u8, owner).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    fn draw_cell(&self, frame: &mut Frame, skin: &Skin, loc: GridLoc) {
+        let (sprite, label, opacity, owner) = self.appearance(loc);
+        let bounds = Rectangle::new(self.origin(loc), Size::new(self.side, self.side));
+        frame.draw_image(
+            bounds,
+            canvas::Image::new(sprite.handle(skin).clone())
+                .filter_method(FilterMethod::Nearest)
+                .opacity(opacity)
+                .snap(true),
+        );
+
+        // Numbers are sized for 16 pixel sprites and scale with the cell.
+        let scale = self.side / 16.0;
+        let label = match label {
+            Some(label @ (Label::Number(..) | Label::Question)) => Some(canvas::Text {
+                content: match label {
+                    Label::Number(number) => number.to_string(),
+                    _ => "?".to_string(),
+                },
+                position: bounds.center(),
+                size: Pixels(14.0 * scale),
+                align_x: Alignment::Center,
+                align_y: Vertical::Center,
+                ..canvas::Text::default()
+            }),
+            Some(Label::Count(count)) => Some(canvas::Text {
+                content: count.to_string(),
+                position: Point::new(bounds.x + bounds.width, bounds.y + bounds.height)
+                    - iced::Vector::new(2.0, 2.0) * scale,
+                size: Pixels(12.0 * scale),
+                align_x: Alignment::Right,
+                align_y: Vertical::Bottom,
+                ..canvas::Text::default()
+            }),
+            None => None,
+        };
+        if let Some(label) = label {
+            frame.fill_text(canvas::Text {
+                color: Color::BLACK,
+                ..label
+            });
+        }
+        if let Some(player) = owner {
+            self.outline(frame, loc, player_color(player));
+        }
+    }
+
+    /// Outlines a cell on the inside of its edges.
+    fn outline(&self, frame: &mut Frame, loc: GridLoc, color: Color) {
+        let width = 3.0_f32.min(self.side / 4.0);
+        frame.stroke_rectangle(
+            self.origin(loc) + iced::Vector::new(width, width) / 2.0,
+            Size::new(self.side - width, self.side - width),
+            Stroke::default().with_color(color).with_width(width),
+        );
+    }
+}
+
+impl Program<Message> for BoardCanvas<'_> {
+    type State = BoardState;
+
+    fn update(
+        &self,
+        state: &mut BoardState,
+        event: &Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Option<Action<Message>> {
+        let loc = cursor
+            .position_in(bounds)
+            .and_then(|point| self.loc_at(point));
+        match event {
+            Event::Mouse(mouse::Event::CursorMoved { .. }) if self.track_pointer => {
+                let loc = loc?;
+                (state.pointer != Some(loc)).then(|| {
+                    state.pointer = Some(loc);
+                    Action::publish(Message::CoopCursor(loc))
+                })
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(button)) => {
+                let both = match button {
+                    mouse::Button::Left => {
+                        state.left_held = true;
+                        state.right_held
+                    }
+                    mouse::Button::Right => {
+                        state.right_held = true;
+                        state.left_held
+                    }
+                    _ => false,
+                };
+                let loc = loc?;
+                let animating = self
+                    .animation
+                    .is_some_and(|animation| animation.is_running(self.now));
+                let message = match (self.input, button) {
+                    // Moves end the animation on their own, but a finished game needs a way to.
+                    (BoardInput::Disabled, _) if animating => Message::AnimationSkip,
+                    (BoardInput::FirstClick, mouse::Button::Left | mouse::Button::Right) => {
+                        Message::RevealClick(loc)
+                    }
+                    (BoardInput::Edit, mouse::Button::Left) => Message::RevealClick(loc),
+                    (BoardInput::Edit, mouse::Button::Right) => Message::FlagClick(loc),
+                    (BoardInput::Play(mode), mouse::Button::Left | mouse::Button::Right) => {
+                        let cell_type = self.cells.cell_type(loc)?;
+                        let left = matches!(button, mouse::Button::Left);
+                        match (cell_type, self.chord_style) {
+                            (CellType::Revealed, ChordStyle::Click) if left => {
+                                Message::ChordClick(loc)
+                            }
+                            (CellType::Revealed, ChordStyle::BothButtons) if both => {
+                                Message::ChordClick(loc)
+                            }
+                            (CellType::Revealed, _) => return None,
+                            // Revealing a flagged cell does nothing, so it needs no special case.
+                            _ if left => mode.apply(Message::RevealClick(loc)),
+                            _ => mode.apply(Message::FlagClick(loc)),
+                        }
+                    }
+                    _ => return None,
+                };
+                Some(Action::publish(message).and_capture())
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(button)) => {
+                match button {
+                    mouse::Button::Left => state.left_held = false,
+                    mouse::Button::Right => state.right_held = false,
+                    _ => {}
+                }
+                let released = matches!(button, mouse::Button::Left)
+                    && loc.is_some()
+                    && !matches!(self.input, BoardInput::Disabled);
+                released.then(|| Action::publish(Message::ClickRelease))
+            }
+            _ => None,
+        }
+    }
+
+    fn draw(
+        &self,
+        state: &BoardState,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let skin = self.skin;
+        let region_rows = self.size.rows.div_ceil(REGION);
+        let region_cols = self.size.cols.div_ceil(REGION);
+        let mut regions = state.regions.borrow_mut();
+        regions.retain(|&(row, col), _| row < region_rows && col < region_cols);
+
+        // Highlights and animations change cells without a new revision, so
+        // blocks are compared on every frame they play, and once after they end.
+        let playing = self
+            .animation
+            .is_some_and(|animation| animation.is_running(self.now))
+            || matches!(self.cells, BoardCells::Grid(grid) if grid.is_animating(self.now));
+        let drawn = (!playing).then(|| {
+            let mut hasher = DefaultHasher::new();
+            (
+                self.cells.revision(),
+                self.topology,
+                (self.size.rows, self.size.cols),
+                Arc::as_ptr(skin) as usize,
+                self.side.to_bits(),
+            )
+                .hash(&mut hasher);
+            hasher.finish()
+        });
+        let unchanged = state.drawn.replace(drawn) == drawn && drawn.is_some();
+
+        let mut geometries = Vec::with_capacity(region_rows * region_cols + 1);
+        for region in (0..region_rows).flat_map(|row| (0..region_cols).map(move |col| (row, col))) {
+            let cached = regions.get(&region).filter(|_| unchanged);
+            if cached.is_none() {
+                let fingerprint = self.fingerprint(region, skin);
+                let (drawn, cache) = regions
+                    .entry(region)
+                    .or_insert_with(|| (fingerprint, Cache::new()));
+                if *drawn != fingerprint {
+                    *drawn = fingerprint;
+                    cache.clear();
+                }
+            }
+            let (_, cache) = &regions[&region];
+            geometries.push(cache.draw_with_bounds(
+                renderer,
+                self.region_bounds(region),
+                |frame| {
+                    for loc in self.region_locs(region) {
+                        self.draw_cell(frame, skin, loc);
+                    }
+                },
+            ));
+        }
+
+        // Other players' pointers move often, so they are drawn over the cached blocks.
+        if !self.cursors.is_empty() {
+            let mut frame = Frame::new(renderer, bounds.size());
+            for &(loc, player) in &self.cursors {
+                self.outline(&mut frame, loc, player_color(player));
+            }
+            geometries.push(frame.into_geometry());
+        }
+        geometries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loc_at() {
+        let skin = Arc::new(Skin::builtin());
+        let mut board = BoardCanvas {
+            cells: BoardCells::Hidden,
+            topology: Topology::Square,
+            size: GridSize { rows: 3, cols: 4 },
+            side: 16.0,
+            now: Instant::now(),
+            input: BoardInput::Disabled,
+            chord_style: ChordStyle::Click,
+            track_pointer: false,
+            cursors: Vec::new(),
+            animation: None,
+            skin: &skin,
+        };
+        assert_eq!(
+            board.loc_at(Point::new(20.0, 40.0)),
+            Some(GridLoc { row: 2, col: 1 })
+        );
+        assert_eq!(board.loc_at(Point::new(64.0, 0.0)), None);
+        assert_eq!(board.loc_at(Point::new(0.0, 48.0)), None);
+
+        // Odd rows of hexagonal boards start half a cell in.
+        board.topology = Topology::Hex;
+        assert_eq!(board.bounds(), Size::new(72.0, 48.0));
+        assert_eq!(board.loc_at(Point::new(4.0, 20.0)), None);
+        assert_eq!(
+            board.loc_at(Point::new(70.0, 20.0)),
+            Some(GridLoc { row: 1, col: 3 })
+        );
+        assert_eq!(
+            board.loc_at(Point::new(4.0, 36.0)),
+            Some(GridLoc { row: 2, col: 0 })
+        );
+    }
+}
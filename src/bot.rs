@@ -0,0 +1,477 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::mpsc,
+    thread,
+};
+
+use iced::futures::channel::mpsc as async_mpsc;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cell::CellType,
+    grid::{CellChordResult, CellRevealResult, GridConfig, GridLoc, GridSize, SafeZone},
+    message::Message,
+    rating::Rating,
+    share::ShareCode,
+    state::{Difficulty, GameState},
+    topology::{Neighborhood, Topology},
+};
+
+/// A request sent by a bot, one JSON object per line.
+///
+/// ```json
+/// {"cmd": "new", "difficulty": "hard", "seed": 42, "rating": "medium"}
+/// {"cmd": "new", "safe_region": [{"row": 0, "col": 0}, {"row": 0, "col": 1}]}
+/// {"cmd": "reveal", "row": 3, "col": 4}
+/// {"cmd": "flag", "row": 0, "col": 1}
+/// {"cmd": "flag_all", "row": 0, "col": 1}
+/// {"cmd": "chord", "row": 3, "col": 4}
+/// {"cmd": "state"}
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    /// Starts a new game waiting for its first reveal.
+    New(NewGame),
+    Reveal(GridLoc),
+    Flag(GridLoc),
+    /// Flags a cell with as many flags as it can hold.
+    FlagAll(GridLoc),
+    Chord(GridLoc),
+    /// Returns the board without changing it.
+    State,
+}
+
+/// Parameters of a new game. Explicit sizes override the difficulty preset.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct NewGame {
+    pub difficulty: Option<String>,
+    pub rows: Option<usize>,
+    pub cols: Option<usize>,
+    pub mines: Option<usize>,
+    #[serde(default)]
+    pub topology: Topology,
+    #[serde(default)]
+    pub wrap: bool,
+    #[serde(default)]
+    pub neighborhood: Neighborhood,
+    pub mines_per_cell: Option<u8>,
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub opening: bool,
+    #[serde(default)]
+    pub no_guess: bool,
+    pub rating: Option<Rating>,
+    /// Cells kept free of mines besides the first reveal, taking the place of `opening`.
+    #[serde(default)]
+    pub safe_region: Vec<GridLoc>,
+}
+
+impl NewGame {
+    /// Resolves the requested board configuration, rejecting impossible ones.
+    pub fn config(&self) -> Result<GridConfig, String> {
+        let mut config = match &self.difficulty {
+            Some(name) => Difficulty::DIFF_ALL
+                .iter()
+                .find(|difficulty| difficulty.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("unknown difficulty `{name}`"))?
+                .state
+                .clone(),
+            None => Difficulty::default().state.clone(),
+        };
+        config.size.rows = self.rows.unwrap_or(config.size.rows);
+        config.size.cols = self.cols.unwrap_or(config.size.cols);
+        config.mines = self.mines.unwrap_or(config.mines);
+        config.topology = self.topology;
+        config.wrap = self.wrap;
+        config.neighborhood = self.neighborhood;
+        config.mines_per_cell = self.mines_per_cell.unwrap_or(1);
+        config.validate()?;
+        let size = config.size;
+        if let Some(loc) = self
+            .safe_region
+            .iter()
+            .find(|loc| loc.row >= size.rows || loc.col >= size.cols)
+        {
+            return Err(format!("({}, {}) is outside the board", loc.row, loc.col));
+        }
+        let safe_zone = self.safe_zone();
+        if matches!(safe_zone, SafeZone::Region(..)) && config.mines > config.max_mines(&safe_zone)
+        {
+            return Err("the safe region leaves no room for the mines".to_string());
+        }
+        Ok(config)
+    }
+
+    pub fn safe_zone(&self) -> SafeZone {
+        if !self.safe_region.is_empty() {
+            SafeZone::Region(self.safe_region.iter().copied().collect())
+        } else if self.opening {
+            SafeZone::Opening
+        } else {
+            SafeZone::Cell
+        }
+    }
+}
+
+impl Request {
+    /// Turns a move into the message the window would send for the same click,
+    /// or explains why the move is not allowed in the current state.
+    pub fn to_message(&self, state: &GameState) -> Result<Message, String> {
+        let (loc, message) = match *self {
+            Request::Reveal(loc) => (loc, Message::RevealClick(loc)),
+            Request::Flag(loc) => (loc, Message::FlagClick(loc)),
+            Request::FlagAll(loc) => (loc, Message::FlagAll(loc)),
+            Request::Chord(loc) => (loc, Message::ChordClick(loc)),
+            Request::New(..) | Request::State => return Ok(Message::NoOp),
+        };
+        let size = match state {
+            GameState::Initialized(config) if matches!(self, Request::Reveal(..)) => config.size,
+            GameState::Started(grid) => GridSize {
+                rows: grid.rows(),
+                cols: grid.cols(),
+            },
+            GameState::Initialized(..) => return Err("the first move must be a reveal".into()),
+            GameState::CreationScreen(..) => return Err("no game in progress".into()),
+            GameState::Won(..) | GameState::Lost(..) => return Err("the game is over".into()),
+        };
+        if loc.row >= size.rows || loc.col >= size.cols {
+            return Err(format!("({}, {}) is outside the board", loc.row, loc.col));
+        }
+        Ok(message)
+    }
+}
+
+/// The outcome of a request: the visible board, or why the request failed.
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum Response {
+    Board(BoardView),
+    Error { error: String },
+}
+
+/// Whether a game is waiting, in progress or finished.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Ready,
+    Playing,
+    Won,
+    Lost,
+}
+
+/// Everything a player could see on screen.
+///
+/// Each row of `board` is a string with one character per cell: `#` hidden,
+/// `F` flagged, `*` a revealed mine and `0`-`9` a revealed number, continuing with
+/// `a` for 10 up to `z` for 35 on boards with larger neighborhoods.
+#[derive(Clone, Debug, Serialize)]
+pub struct BoardView {
+    pub status: Status,
+    pub rows: usize,
+    pub cols: usize,
+    pub mines: usize,
+    pub flags: usize,
+    pub board: Vec<String>,
+    pub code: Option<String>,
+}
+
+impl Response {
+    pub fn error(error: impl Into<String>) -> Self {
+        Self::Error {
+            error: error.into(),
+        }
+    }
+
+    /// Describes the board as the player currently sees it.
+    pub fn board(state: &GameState, share_code: Option<&ShareCode>) -> Self {
+        let (status, grid) = match state {
+            GameState::CreationScreen(config) | GameState::Initialized(config) => {
+                let GridSize { rows, cols } = config.size;
+                return Self::Board(BoardView {
+                    status: Status::Ready,
+                    rows,
+                    cols,
+                    mines: config.mines,
+                    flags: 0,
+                    board: vec!["#".repeat(cols); rows],
+                    code: None,
+                });
+            }
+            GameState::Started(grid) => (Status::Playing, grid),
+            GameState::Won(grid) => (Status::Won, grid),
+            GameState::Lost(grid) => (Status::Lost, grid),
+        };
+        let board = (0..grid.rows())
+            .map(|row| {
+                (0..grid.cols())
+                    .map(|col| {
+                        let cell = grid.get(row, col).unwrap();
+                        match cell.cell_type {
+                            CellType::Hidden => '#',
+                            CellType::Flagged(..) => 'F',
+                            CellType::Revealed if cell.is_mine() => '*',
+                            CellType::Revealed => char::from_digit(
+                                grid.count_neighboring_mines(GridLoc { row, col }).into(),
+                                36,
+                            )
+                            .unwrap_or('?'),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Self::Board(BoardView {
+            status,
+            rows: grid.rows(),
+            cols: grid.cols(),
+            mines: grid.count_mines(),
+            flags: grid.count_flags(),
+            board,
+            code: share_code.map(ShareCode::to_string),
+        })
+    }
+}
+
+/// A standalone game played entirely through the protocol.
+#[derive(Debug, Default)]
+pub struct Session {
+    state: GameState,
+    new_game: NewGame,
+    share_code: Option<ShareCode>,
+}
+
+impl Session {
+    pub fn handle(&mut self, request: Request) -> Response {
+        let message = match request.to_message(&self.state) {
+            Ok(message) => message,
+            Err(error) => return Response::error(error),
+        };
+        match (request, std::mem::take(&mut self.state)) {
+            (Request::State, state) => self.state = state,
+            (Request::New(new_game), state) => match new_game.config() {
+                Ok(config) => {
+                    self.state = GameState::Initialized(config);
+                    self.new_game = new_game;
+                    self.share_code = None;
+                }
+                Err(error) => {
+                    self.state = state;
+                    return Response::error(error);
+                }
+            },
+            (_, GameState::Initialized(config)) => {
+                let Message::RevealClick(start) = message else {
+                    unreachable!("Only reveals can start a game");
+                };
+                let code = ShareCode {
+                    config,
+                    start,
+                    seed: self.new_game.seed.take().unwrap_or_else(rand::random),
+                    opening: self.new_game.opening,
+                    no_guess: self.new_game.no_guess,
+                    rating: self.new_game.rating,
+                    layout: None,
+                };
+                let safe_zone = self.new_game.safe_zone();
+                match code.generate_in(&safe_zone) {
+                    Ok(grid) => {
+                        self.state = GameState::resolve(grid, false);
+                        // A code cannot describe a safe region, so it would deal another board.
+                        self.share_code =
+                            (!matches!(safe_zone, SafeZone::Region(..))).then_some(code);
+                    }
+                    Err(error) => {
+                        self.state = GameState::Initialized(code.config);
+                        return Response::error(error);
+                    }
+                }
+            }
+            (_, GameState::Started(mut grid)) => {
+                let hit_mine = match message {
+                    Message::RevealClick(loc) => {
+                        matches!(grid.cascade_reveal(loc), CellRevealResult::Mine)
+                    }
+                    Message::ChordClick(loc) => {
+                        matches!(grid.chord_reveal(loc), CellChordResult::Mines(..))
+                    }
+                    Message::FlagClick(loc) => {
+                        grid.flag_cell(loc);
+                        false
+                    }
+                    Message::FlagAll(loc) => {
+                        grid.set_flags(loc, grid.mines_per_cell());
+                        false
+                    }
+                    _ => false,
+                };
+                self.state = GameState::resolve(grid, hit_mine);
+            }
+            (_, state) => self.state = state,
+        }
+        Response::board(&self.state, self.share_code.as_ref())
+    }
+}
+
+/// A request forwarded to the window, with a channel for its response.
+#[derive(Clone, Debug)]
+pub struct BotCommand {
+    pub request: Request,
+    pub reply: mpsc::SyncSender<Response>,
+}
+
+/// Answers every request line on `stream` with `handle` until the bot disconnects.
+fn serve_connection(
+    stream: TcpStream,
+    mut handle: impl FnMut(Request) -> Response,
+) -> io::Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str(&line) {
+            Ok(request) => handle(request),
+            Err(error) => Response::error(format!("invalid request: {error}")),
+        };
+        serde_json::to_writer(&mut writer, &response)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Runs a headless server where every connection plays its own game.
+pub fn run_server(addr: SocketAddr) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    println!("Listening for bots on {}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                eprintln!("Could not accept a bot: {error}");
+                continue;
+            }
+        };
+        thread::spawn(move || {
+            let mut session = Session::default();
+            serve_connection(stream, |request| session.handle(request))
+        });
+    }
+    Ok(())
+}
+
+/// Serves the game shown in the window, forwarding each request to it as a message.
+///
+/// Meant for `Subscription::run_with`, so bots can be watched playing live.
+pub fn window_requests(addr: &SocketAddr) -> async_mpsc::UnboundedReceiver<Message> {
+    let (sender, receiver) = async_mpsc::unbounded();
+    let addr = *addr;
+    thread::spawn(move || {
+        let listener = match TcpListener::bind(addr) {
+            Ok(listener) => listener,
+            Err(error) => {
+                eprintln!("Could not listen for bots on {addr}: {error}");
+                return;
+            }
+        };
+        for stream in listener.incoming().flatten() {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                serve_connection(stream, |request| {
+                    let (reply, response) = mpsc::sync_channel(1);
+                    let command = BotCommand { request, reply };
+                    if sender.unbounded_send(Message::Bot(command)).is_err() {
+                        return Response::error("the game was closed");
+                    }
+                    response
+                        .recv()
+                        .unwrap_or_else(|_| Response::error("the game was closed"))
+                })
+            });
+        }
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(json: &str) -> Request {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn board(response: Response) -> BoardView {
+        match response {
+            Response::Board(board) => board,
+            Response::Error { error } => panic!("unexpected error: {error}"),
+        }
+    }
+
+    #[test]
+    fn test_session_plays_a_game() {
+        let mut session = Session::default();
+        let view =
+            board(session.handle(request(r#"{"cmd": "new", "seed": 6767, "opening": true}"#)));
+        assert!(matches!(view.status, Status::Ready));
+        assert_eq!(view.board, vec!["#########"; 9]);
+
+        let view = board(session.handle(request(r#"{"cmd": "state"}"#)));
+        assert!(matches!(view.status, Status::Ready));
+
+        let view = board(session.handle(request(r#"{"cmd": "reveal", "row": 4, "col": 4}"#)));
+        assert!(!matches!(view.status, Status::Ready | Status::Lost));
+        assert_eq!(view.board[4].as_bytes()[4], b'0');
+        assert!(view.code.unwrap().ends_with("-o"));
+    }
+
+    #[test]
+    fn test_session_keeps_safe_region_clear() {
+        let mut session = Session::default();
+        let region: Vec<_> = (0..9)
+            .map(|col| format!(r#"{{"row": 8, "col": {col}}}"#))
+            .collect();
+        let new = format!(
+            r#"{{"cmd": "new", "rows": 9, "cols": 9, "mines": 71, "seed": 6767, "safe_region": [{}]}}"#,
+            region.join(", ")
+        );
+        board(session.handle(request(&new)));
+        let view = board(session.handle(request(r#"{"cmd": "reveal", "row": 0, "col": 0}"#)));
+        assert!(view.code.is_none());
+        let GameState::Started(grid) = &session.state else {
+            panic!("the game should go on");
+        };
+        assert!((0..9).all(|col| !grid.get(8, col).unwrap().is_mine()));
+
+        let crowded = new.replace("71", "72");
+        assert!(matches!(
+            session.handle(request(&crowded)),
+            Response::Error { .. }
+        ));
+    }
+
+    #[test]
+    fn test_session_rejects_invalid_moves() {
+        let mut session = Session::default();
+        let flag = r#"{"cmd": "flag", "row": 0, "col": 0}"#;
+        assert!(matches!(
+            session.handle(request(flag)),
+            Response::Error { .. }
+        ));
+
+        let new = r#"{"cmd": "new", "rows": 5, "cols": 5, "mines": 25}"#;
+        assert!(matches!(
+            session.handle(request(new)),
+            Response::Error { .. }
+        ));
+
+        let reveal = r#"{"cmd": "reveal", "row": 9, "col": 0}"#;
+        assert!(matches!(
+            session.handle(request(reveal)),
+            Response::Error { .. }
+        ));
+    }
+}
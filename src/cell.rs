@@ -1,23 +1,35 @@
-use std::time::Instant;
+use std::borrow::Cow;
 
 use crate::assets::*;
-use crate::message::Message;
-use iced::{Animation, Element, animation::Easing, widget::mouse_area};
+use iced::{Animation, animation::Easing};
 
 const DIGIT_LOOKUP: [&str; 9] = ["0", "1", "2", "3", "4", "5", "6", "7", "8"];
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
 pub enum CellType {
     #[default]
     Hidden,
     Revealed,
-    Flagged,
+    /// Marked by the player as holding this many mines.
+    Flagged(u8),
+}
+
+/// A number written over a cell's sprite.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Label {
+    /// Neighboring mines past the sprites' 8, in the middle of the cell.
+    Number(u8),
+    /// Mines or flags on a multi-mine cell, in its corner.
+    Count(u8),
+    /// A question mark the player left on a hidden cell, in the middle of the cell.
+    Question,
 }
 
 #[derive(Clone, Debug)]
 pub struct Cell {
     pub cell_type: CellType,
-    pub is_mine: bool,
+    /// Number of mines in the cell, which only exceeds 1 on multi-mine boards.
+    pub mines: u8,
     pub highlight: Animation<bool>,
 }
 
@@ -25,57 +37,48 @@ impl Default for Cell {
     fn default() -> Self {
         Self {
             cell_type: CellType::default(),
-            is_mine: false,
+            mines: 0,
             highlight: Animation::new(false).easing(Easing::EaseOutCubic).slow(),
         }
     }
 }
 
 impl Cell {
-    pub fn to_string(&self, neighboring_mines: u8) -> &'static str {
+    pub fn is_mine(&self) -> bool {
+        self.mines > 0
+    }
+
+    pub fn to_string(&self, neighboring_mines: u8) -> Cow<'static, str> {
         match self.cell_type {
-            CellType::Hidden => "⬛",
+            CellType::Hidden => "⬛".into(),
             CellType::Revealed => {
-                if self.is_mine {
-                    "💣"
+                if self.is_mine() {
+                    "💣".into()
                 } else if neighboring_mines == 0 {
-                    "  "
+                    "  ".into()
                 } else {
-                    DIGIT_LOOKUP[neighboring_mines as usize]
+                    match DIGIT_LOOKUP.get(neighboring_mines as usize) {
+                        Some(digit) => (*digit).into(),
+                        None => neighboring_mines.to_string().into(),
+                    }
                 }
             }
-            CellType::Flagged => "🚩",
+            CellType::Flagged(..) => "🚩".into(),
         }
     }
-    pub fn display(
-        &self,
-        neighboring_mines: u8,
-        on_reveal: Message,
-        on_chord: Message,
-        on_flag: Message,
-        now: Instant,
-    ) -> Element<'static, Message> {
-        let sprite = match self.cell_type {
-            CellType::Hidden => get_unrevealed_cell_image(),
-            CellType::Revealed => {
-                if self.is_mine {
-                    get_mine_image()
-                } else {
-                    get_cell_image(neighboring_mines as usize)
-                }
+
+    /// Returns the tile showing this cell and any number written over it.
+    pub fn sprite(&self, neighboring_mines: u8) -> (Sprite, Option<Label>) {
+        let count = |count: u8| (count > 1).then_some(Label::Count(count));
+        match self.cell_type {
+            CellType::Hidden => (Sprite::Unrevealed, None),
+            CellType::Revealed if self.is_mine() => (Sprite::Mine, count(self.mines)),
+            // Sprites only go up to 8, so larger numbers are written over an empty cell.
+            CellType::Revealed if neighboring_mines as usize >= DIGIT_LOOKUP.len() => {
+                (Sprite::Number(0), Some(Label::Number(neighboring_mines)))
             }
-            CellType::Flagged => get_flag_image(),
+            CellType::Revealed => (Sprite::Number(neighboring_mines), None),
+            CellType::Flagged(flags) => (Sprite::Flag, count(flags)),
         }
-        .opacity(self.highlight.interpolate(1.0, 0.6, now));
-
-        mouse_area(sprite)
-            .on_press(match self.cell_type {
-                // Flagged calls won't do anything, so we don't need to handle them here
-                CellType::Hidden | CellType::Flagged => on_reveal,
-                CellType::Revealed => on_chord,
-            })
-            .on_right_press(on_flag)
-            .on_release(Message::ClickRelease)
-            .into()
     }
 }
@@ -0,0 +1,203 @@
+use std::{net::SocketAddr, path::Path};
+
+use clap::{CommandFactory, Parser, Subcommand, error::ErrorKind};
+
+use crate::{
+    coop::CoopRole,
+    grid::GridConfig,
+    layout,
+    rating::Rating,
+    share::ShareCode,
+    state::Difficulty,
+    topology::{Neighborhood, Topology},
+};
+
+/// A minesweeper game built with Iced.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Difficulty preset to start with: easy, medium, hard or extreme.
+    #[arg(global = true, short, long, value_parser = parse_difficulty)]
+    pub difficulty: Option<&'static Difficulty>,
+
+    /// Number of rows, overriding the preset.
+    #[arg(global = true, long)]
+    pub rows: Option<usize>,
+
+    /// Number of columns, overriding the preset.
+    #[arg(global = true, long)]
+    pub cols: Option<usize>,
+
+    /// Number of mines, overriding the preset.
+    #[arg(global = true, long)]
+    pub mines: Option<usize>,
+
+    /// Shape of the cells: square or hex.
+    #[arg(global = true, long)]
+    pub topology: Option<Topology>,
+
+    /// Make the edges wrap around, so every cell has a full set of neighbors.
+    #[arg(global = true, long)]
+    pub wrap: bool,
+
+    /// Cells counted by the numbers: standard, orthogonal, knight or wide (5x5).
+    #[arg(global = true, long)]
+    pub neighborhood: Option<Neighborhood>,
+
+    /// Most mines a single cell can hold, from 1 (classic) to 5.
+    #[arg(global = true, long, default_value_t = 1)]
+    pub mines_per_cell: u8,
+
+    /// Seed for mine placement, making the board reproducible.
+    #[arg(global = true, long)]
+    pub seed: Option<u64>,
+
+    /// Share code of a board to open directly.
+    #[arg(global = true, long, conflicts_with_all = ["difficulty", "rows", "cols", "mines", "topology", "wrap", "neighborhood", "mines_per_cell", "seed", "no_guess", "rating"])]
+    pub code: Option<ShareCode>,
+
+    /// Board file saved by the board editor to open directly.
+    #[arg(global = true, long, value_name = "FILE", value_parser = parse_board, conflicts_with_all = ["code", "difficulty", "rows", "cols", "mines", "topology", "wrap", "neighborhood", "mines_per_cell", "seed", "no_guess", "rating"])]
+    pub board: Option<ShareCode>,
+
+    /// Only generate boards that can be solved without guessing.
+    #[arg(global = true, long)]
+    pub no_guess: bool,
+
+    /// Only generate boards with this rating: easy, medium, hard or evil.
+    #[arg(global = true, long)]
+    pub rating: Option<Rating>,
+
+    /// Scale factor applied to the window and everything in it.
+    #[arg(global = true, long, default_value_t = 1.0)]
+    pub scale: f32,
+
+    /// Let bots play the game shown in the window through the JSON protocol on this address.
+    #[arg(long, value_name = "ADDR")]
+    pub bot_listen: Option<SocketAddr>,
+
+    /// Host a co-op game that other windows can join on this address.
+    #[arg(long, value_name = "ADDR", conflicts_with = "join")]
+    pub host: Option<SocketAddr>,
+
+    /// Join the co-op game hosted on this address, playing the host's board.
+    #[arg(long, value_name = "ADDR")]
+    pub join: Option<SocketAddr>,
+
+    /// Make the hosted game a race where every player clears their own copy of the board.
+    #[arg(long, requires = "host")]
+    pub race: bool,
+}
+
+/// Alternative ways of running the game.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Play full-screen in the terminal instead of opening a window.
+    Tui,
+    /// Play many boards with the solver and report how it fared.
+    Bench {
+        /// Number of boards to play, using consecutive seeds starting at `--seed`.
+        #[arg(long, default_value_t = 100)]
+        boards: u64,
+        /// Keep the first click's neighbors free of mines. With `--code`, the code's
+        /// own setting is used instead.
+        #[arg(long)]
+        opening: bool,
+        /// Print the report as JSON instead of a table.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Serve games to bots over a local TCP socket without opening a window.
+    Bot {
+        /// Address to listen on.
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        listen: SocketAddr,
+    },
+}
+
+/// Everything the game needs to know from the command line to boot.
+#[derive(Clone, Debug)]
+pub struct LaunchOptions {
+    pub state: LaunchState,
+    pub seed: Option<u64>,
+    pub no_guess: bool,
+    pub rating: Option<Rating>,
+    pub scale: f32,
+    pub bot_listen: Option<SocketAddr>,
+    pub coop: Option<CoopRole>,
+    pub race: bool,
+}
+
+/// The board the game opens on.
+#[derive(Clone, Debug)]
+pub enum LaunchState {
+    /// An empty board waiting for the first click.
+    Config(GridConfig),
+    /// A board reproduced from a share code or board file, with its first click already played.
+    Code(ShareCode),
+}
+
+fn parse_difficulty(name: &str) -> Result<&'static Difficulty, String> {
+    Difficulty::named(name).ok_or_else(|| {
+        let names: Vec<_> = Difficulty::DIFF_ALL.iter().map(|d| d.name).collect();
+        format!("expected one of {}", names.join(", "))
+    })
+}
+
+fn parse_board(path: &str) -> Result<ShareCode, String> {
+    layout::load(Path::new(path))
+}
+
+impl Cli {
+    /// Validates the arguments and turns them into launch options, starting
+    /// from `preset` when no difficulty is given. Exits with a usage error if
+    /// the requested board is impossible.
+    pub fn into_launch_options(self, preset: &Difficulty) -> LaunchOptions {
+        if !(self.scale.is_finite() && self.scale > 0.0) {
+            Self::fail("--scale must be a positive number");
+        }
+        let state = match self.code.or(self.board) {
+            Some(code) => LaunchState::Code(code),
+            None => {
+                let mut config = match self.difficulty {
+                    Some(difficulty) => difficulty.state.clone(),
+                    None => preset.state.clone(),
+                };
+                config.size.rows = self.rows.unwrap_or(config.size.rows);
+                config.size.cols = self.cols.unwrap_or(config.size.cols);
+                config.mines = self.mines.unwrap_or(config.mines);
+                config.topology = self.topology.unwrap_or(config.topology);
+                config.wrap = self.wrap;
+                config.neighborhood = self.neighborhood.unwrap_or_default();
+                config.mines_per_cell = self.mines_per_cell;
+                if let Err(error) = config.validate() {
+                    Self::fail(&error);
+                }
+                LaunchState::Config(config)
+            }
+        };
+        LaunchOptions {
+            state,
+            seed: self.seed,
+            no_guess: self.no_guess,
+            rating: self.rating,
+            scale: self.scale,
+            bot_listen: self.bot_listen,
+            coop: match (self.host, self.join) {
+                (Some(addr), _) => Some(CoopRole::Host(addr)),
+                (None, Some(addr)) => Some(CoopRole::Join(addr)),
+                (None, None) => None,
+            },
+            race: self.race,
+        }
+    }
+
+    fn fail(message: &str) -> ! {
+        Self::command()
+            .error(ErrorKind::ValueValidation, message)
+            .exit()
+    }
+}
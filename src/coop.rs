@@ -0,0 +1,398 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex, mpsc},
+    thread,
+};
+
+use iced::futures::channel::mpsc as async_mpsc;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bot::Request,
+    cell::CellType,
+    grid::{GridConfig, GridLoc},
+    message::Message,
+    state::GameState,
+};
+
+/// Number the host plays as; joining players are numbered from 1.
+pub const HOST: u8 = 0;
+
+/// Which side of a co-op game this window is on.
+#[derive(Clone, Copy, Debug)]
+pub enum CoopRole {
+    /// Owns the board and accepts players on this address.
+    Host(SocketAddr),
+    /// Plays the board of the host at this address.
+    Join(SocketAddr),
+}
+
+/// What the players of a co-op game send each other, one JSON object per line.
+///
+/// The host owns the board: players send it their moves, and it sends every
+/// move back to everyone in the order it was played. Boards are reproducible
+/// from their share code, so replaying the same moves keeps all boards identical.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CoopMessage {
+    /// Tells a new player which number they play as, and whether the game is a race.
+    Welcome {
+        player: u8,
+        #[serde(default)]
+        race: bool,
+    },
+    /// An empty board is waiting for its first reveal.
+    Setup { config: GridConfig },
+    /// The board being played, with its first reveal already made.
+    Start { code: String },
+    /// A reveal, flag or chord.
+    Move { request: Request },
+    /// Where a player's pointer is on the board.
+    Cursor { player: u8, loc: GridLoc },
+    /// How far a player got on their copy of the race board.
+    Progress { player: u8, progress: Progress },
+    /// A player disconnected.
+    Left { player: u8 },
+}
+
+/// How far a racer got on their copy of the board.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Progress {
+    /// Share of the safe cells revealed, in percent.
+    pub cleared: u8,
+    pub status: RaceStatus,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RaceStatus {
+    Playing,
+    Won,
+    Lost,
+}
+
+impl Progress {
+    /// Returns the progress shown by a game, or `None` before its board is dealt.
+    ///
+    /// Lost boards have every cell revealed, so they keep the share cleared
+    /// before the mine, taken from `previous`.
+    pub fn of(state: &GameState, previous: Option<Progress>) -> Option<Self> {
+        let (cleared, status) = match state {
+            GameState::CreationScreen(..) | GameState::Initialized(..) => return None,
+            GameState::Started(grid) => {
+                let (safe, revealed) = grid
+                    .locs()
+                    .filter_map(|loc| grid.get(loc.row, loc.col))
+                    .filter(|cell| !cell.is_mine())
+                    .fold((0, 0), |(safe, revealed), cell| {
+                        let is_revealed = matches!(cell.cell_type, CellType::Revealed);
+                        (safe + 1, revealed + usize::from(is_revealed))
+                    });
+                // Only a won board is fully cleared, even with every safe cell revealed.
+                let cleared = (revealed * 100 / safe.max(1)).min(99);
+                (cleared as u8, RaceStatus::Playing)
+            }
+            GameState::Won(..) => (100, RaceStatus::Won),
+            GameState::Lost(..) => (
+                previous.map_or(0, |progress| progress.cleared),
+                RaceStatus::Lost,
+            ),
+        };
+        Some(Self { cleared, status })
+    }
+}
+
+/// News from the network, delivered to the window as messages.
+#[derive(Clone, Debug)]
+pub enum CoopEvent {
+    /// The connection is up and messages can be sent through the link.
+    Connected(CoopLink),
+    /// A player connected to the host.
+    Joined(u8),
+    Received {
+        from: u8,
+        message: CoopMessage,
+    },
+    /// A player disconnected, or the host if this window joined it.
+    Left(u8),
+}
+
+/// Messages waiting to be written to the other players.
+#[derive(Clone, Debug)]
+enum Outgoing {
+    All(CoopMessage),
+    To(u8, CoopMessage),
+}
+
+/// Sends messages to the other players of a co-op game.
+#[derive(Clone, Debug)]
+pub struct CoopLink(mpsc::Sender<Outgoing>);
+
+/// The co-op game this window takes part in.
+#[derive(Debug)]
+pub struct Coop {
+    pub role: CoopRole,
+    link: Option<CoopLink>,
+    /// Number this window plays as.
+    pub player: u8,
+    /// Where the other players' pointers are.
+    pub cursors: HashMap<u8, GridLoc>,
+    /// Moves played on the current board, to bring players who join late up to date.
+    pub moves: Vec<Request>,
+    /// Whether every player races on their own copy of the board instead of sharing it.
+    pub race: bool,
+    /// How far every racer got, this window included.
+    pub progress: HashMap<u8, Progress>,
+    /// The first racer to clear the board.
+    pub winner: Option<u8>,
+}
+
+impl Coop {
+    pub fn new(role: CoopRole, race: bool) -> Self {
+        Self {
+            role,
+            link: None,
+            player: HOST,
+            cursors: HashMap::new(),
+            moves: Vec::new(),
+            race,
+            progress: HashMap::new(),
+            winner: None,
+        }
+    }
+
+    pub fn is_host(&self) -> bool {
+        matches!(self.role, CoopRole::Host(..))
+    }
+
+    pub fn connect(&mut self, link: CoopLink) {
+        self.link = Some(link);
+    }
+
+    /// Forgets what happened on the previous board.
+    pub fn new_board(&mut self) {
+        self.moves.clear();
+        self.progress.clear();
+        self.winner = None;
+    }
+
+    /// Records a racer's progress, in the order the host received it, so the
+    /// first racer reported to have won stays the winner.
+    pub fn record(&mut self, player: u8, progress: Progress) {
+        if progress.status == RaceStatus::Won && self.winner.is_none() {
+            self.winner = Some(player);
+        }
+        self.progress.insert(player, progress);
+    }
+
+    /// Sends a message to every player, or to the host when this window joined one.
+    pub fn send(&self, message: CoopMessage) {
+        if let Some(CoopLink(sender)) = &self.link {
+            let _ = sender.send(Outgoing::All(message));
+        }
+    }
+
+    /// Sends a message to a single player. Only the host knows other players.
+    pub fn send_to(&self, player: u8, message: CoopMessage) {
+        if let Some(CoopLink(sender)) = &self.link {
+            let _ = sender.send(Outgoing::To(player, message));
+        }
+    }
+}
+
+/// Returns the move a message plays on the board, if it is one.
+pub fn move_request(message: &Message) -> Option<Request> {
+    match *message {
+        Message::RevealClick(loc) => Some(Request::Reveal(loc)),
+        Message::ChordClick(loc) => Some(Request::Chord(loc)),
+        Message::FlagClick(loc) => Some(Request::Flag(loc)),
+        Message::FlagAll(loc) => Some(Request::FlagAll(loc)),
+        _ => None,
+    }
+}
+
+fn write_message(mut stream: &TcpStream, message: &CoopMessage) -> io::Result<()> {
+    serde_json::to_writer(&mut stream, message)?;
+    stream.write_all(b"\n")
+}
+
+/// Forwards every message read from `stream` to the window until it disconnects.
+fn read_messages(stream: TcpStream, from: u8, sender: &async_mpsc::UnboundedSender<Message>) {
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        match serde_json::from_str(&line) {
+            Ok(message) => {
+                let event = CoopEvent::Received { from, message };
+                if sender.unbounded_send(Message::Coop(event)).is_err() {
+                    return;
+                }
+            }
+            Err(error) => eprintln!("Ignoring invalid co-op message from player {from}: {error}"),
+        }
+    }
+    let _ = sender.unbounded_send(Message::Coop(CoopEvent::Left(from)));
+}
+
+/// Hosts a co-op game on `addr`, accepting players until the window closes.
+///
+/// Meant for `Subscription::run_with`, like `bot::window_requests`.
+pub fn host(addr: &SocketAddr) -> async_mpsc::UnboundedReceiver<Message> {
+    let (sender, receiver) = async_mpsc::unbounded();
+    let addr = *addr;
+    thread::spawn(move || {
+        let listener = match TcpListener::bind(addr) {
+            Ok(listener) => listener,
+            Err(error) => {
+                eprintln!("Could not host a co-op game on {addr}: {error}");
+                return;
+            }
+        };
+        println!("Hosting a co-op game on {addr}");
+
+        let players: Arc<Mutex<HashMap<u8, TcpStream>>> = Arc::default();
+        let (outgoing, queue) = mpsc::channel();
+        let writers = Arc::clone(&players);
+        thread::spawn(move || {
+            for outgoing in queue {
+                let mut players = writers.lock().unwrap();
+                match outgoing {
+                    // Players that cannot be written to have left, which their reader reports.
+                    Outgoing::All(message) => {
+                        players.retain(|_, stream| write_message(stream, &message).is_ok())
+                    }
+                    Outgoing::To(player, message) => {
+                        if let Some(stream) = players.get(&player) {
+                            let _ = write_message(stream, &message);
+                        }
+                    }
+                }
+            }
+        });
+        let link = CoopLink(outgoing);
+        if sender
+            .unbounded_send(Message::Coop(CoopEvent::Connected(link)))
+            .is_err()
+        {
+            return;
+        }
+
+        for (player, stream) in (HOST + 1..=u8::MAX).zip(listener.incoming().flatten()) {
+            let Ok(writer) = stream.try_clone() else {
+                continue;
+            };
+            players.lock().unwrap().insert(player, writer);
+            let _ = sender.unbounded_send(Message::Coop(CoopEvent::Joined(player)));
+            let sender = sender.clone();
+            let players = Arc::clone(&players);
+            thread::spawn(move || {
+                read_messages(stream, player, &sender);
+                players.lock().unwrap().remove(&player);
+            });
+        }
+    });
+    receiver
+}
+
+/// Joins the co-op game hosted on `addr`.
+///
+/// Meant for `Subscription::run_with`, like `bot::window_requests`.
+pub fn join(addr: &SocketAddr) -> async_mpsc::UnboundedReceiver<Message> {
+    let (sender, receiver) = async_mpsc::unbounded();
+    let addr = *addr;
+    thread::spawn(move || {
+        let stream = match TcpStream::connect(addr) {
+            Ok(stream) => stream,
+            Err(error) => {
+                eprintln!("Could not join the co-op game on {addr}: {error}");
+                return;
+            }
+        };
+        let Ok(writer) = stream.try_clone() else {
+            return;
+        };
+        let (outgoing, queue) = mpsc::channel();
+        thread::spawn(move || {
+            for outgoing in queue {
+                let (Outgoing::All(message) | Outgoing::To(_, message)) = outgoing;
+                if write_message(&writer, &message).is_err() {
+                    break;
+                }
+            }
+        });
+        let link = CoopLink(outgoing);
+        if sender
+            .unbounded_send(Message::Coop(CoopEvent::Connected(link)))
+            .is_ok()
+        {
+            read_messages(stream, HOST, &sender);
+        }
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        grid::{Grid, GridSize},
+        topology::Topology,
+    };
+
+    #[test]
+    fn test_coop_message_format() {
+        let message = CoopMessage::Move {
+            request: Request::Flag(GridLoc { row: 1, col: 2 }),
+        };
+        let json = serde_json::to_string(&message).unwrap();
+        assert_eq!(
+            json,
+            r#"{"kind":"move","request":{"cmd":"flag","row":1,"col":2}}"#
+        );
+        let parsed: CoopMessage = serde_json::from_str(&json).unwrap();
+        assert!(matches!(
+            parsed,
+            CoopMessage::Move {
+                request: Request::Flag(GridLoc { row: 1, col: 2 })
+            }
+        ));
+    }
+
+    #[test]
+    fn test_race_progress() {
+        // The corner cell stays hidden behind the mines around it.
+        let mut grid = Grid::new(GridSize { rows: 5, cols: 5 }, Topology::Square);
+        for (row, col) in [(0, 1), (1, 0)] {
+            grid.get_mut(row, col).unwrap().mines = 1;
+        }
+        grid.cascade_reveal(GridLoc { row: 2, col: 2 });
+        let state = GameState::resolve(grid, false);
+        let progress = Progress::of(&state, None).unwrap();
+        assert_eq!(progress.status, RaceStatus::Playing);
+        assert!(progress.cleared > 0 && progress.cleared < 100);
+
+        let GameState::Started(grid) = state else {
+            panic!("the board should still be playing");
+        };
+        let lost = Progress::of(&GameState::Lost(grid), Some(progress)).unwrap();
+        assert_eq!(lost.cleared, progress.cleared);
+        assert_eq!(lost.status, RaceStatus::Lost);
+
+        let addr = "127.0.0.1:7979".parse().unwrap();
+        let mut coop = Coop::new(CoopRole::Host(addr), true);
+        let won = Progress {
+            cleared: 100,
+            status: RaceStatus::Won,
+        };
+        coop.record(2, lost);
+        coop.record(3, won);
+        coop.record(1, won);
+        assert_eq!(coop.winner, Some(3));
+        coop.new_board();
+        assert_eq!(coop.winner, None);
+        assert!(coop.progress.is_empty());
+    }
+}
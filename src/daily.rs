@@ -0,0 +1,168 @@
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{grid::GridLoc, share::ShareCode, state::Difficulty, storage};
+
+/// File in the data directory holding the results of past daily challenges.
+const HISTORY_FILE: &str = "daily.json";
+
+/// A calendar day in UTC, so everyone gets the same daily board wherever they are.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Date {
+    pub year: i64,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    /// Returns the current day in UTC.
+    pub fn today() -> Self {
+        let elapsed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self::from_days((elapsed.as_secs() / 86_400) as i64)
+    }
+
+    /// Converts a number of days since 1970-01-01 into a date.
+    ///
+    /// Uses Howard Hinnant's `civil_from_days` algorithm for the proleptic Gregorian calendar.
+    fn from_days(days: i64) -> Self {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        Self {
+            year: year_of_era + era * 400 + i64::from(month <= 2),
+            month: month as u8,
+            day: day as u8,
+        }
+    }
+
+    /// Returns the date's number, e.g. `20261018`, which doubles as the board's seed.
+    fn number(self) -> u64 {
+        (self.year * 10_000 + i64::from(self.month) * 100 + i64::from(self.day)) as u64
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Returns the daily challenge board for `date`.
+///
+/// The board cycles through the Easy, Medium and Hard presets day by day, is
+/// generated without guesses and starts with an opening in its center, so
+/// everyone plays exactly the same board.
+pub fn challenge(date: Date) -> ShareCode {
+    let presets = [
+        Difficulty::DIFF_EASY,
+        Difficulty::DIFF_MEDIUM,
+        Difficulty::DIFF_HARD,
+    ];
+    let seed = date.number();
+    let config = presets[(seed % presets.len() as u64) as usize]
+        .state
+        .clone();
+    ShareCode {
+        start: GridLoc {
+            row: config.size.rows / 2,
+            col: config.size.cols / 2,
+        },
+        config,
+        seed,
+        opening: true,
+        no_guess: true,
+        rating: None,
+        layout: None,
+    }
+}
+
+/// How a daily challenge went.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DailyResult {
+    /// The day of the challenge, as `YYYY-MM-DD`.
+    pub date: String,
+    pub won: bool,
+    pub seconds: u64,
+    /// Share code of the board, to replay it later.
+    pub code: String,
+}
+
+/// Results of past daily challenges, kept on disk.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DailyHistory {
+    pub results: Vec<DailyResult>,
+}
+
+impl DailyHistory {
+    /// Loads the history, starting empty if there is none yet.
+    pub fn load() -> Self {
+        storage::load(HISTORY_FILE)
+    }
+
+    /// Returns the result of the challenge on `date`, if it was played.
+    pub fn get(&self, date: Date) -> Option<&DailyResult> {
+        let date = date.to_string();
+        self.results.iter().find(|result| result.date == date)
+    }
+
+    /// Records the first finished attempt at the challenge on `date` and saves
+    /// the history. Later attempts on the same day do not count.
+    pub fn record(&mut self, date: Date, won: bool, time: Duration) {
+        if self.get(date).is_some() {
+            return;
+        }
+        self.results.push(DailyResult {
+            date: date.to_string(),
+            won,
+            seconds: time.as_secs(),
+            code: challenge(date).to_string(),
+        });
+        if let Err(error) = storage::save(HISTORY_FILE, self) {
+            eprintln!("coop-sweeper: could not save daily results: {error}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_from_days() {
+        let date = |year, month, day| Date { year, month, day };
+        assert_eq!(Date::from_days(0), date(1970, 1, 1));
+        assert_eq!(Date::from_days(-1), date(1969, 12, 31));
+        assert_eq!(Date::from_days(11_016), date(2000, 2, 29));
+        assert_eq!(Date::from_days(20_744), date(2026, 10, 18));
+        assert_eq!(date(2026, 10, 18).to_string(), "2026-10-18");
+    }
+
+    #[test]
+    fn test_challenge_is_deterministic() {
+        let today = Date::from_days(20_744);
+        let code = challenge(today);
+        assert_eq!(code.to_string(), challenge(today).to_string());
+        assert!(code.no_guess && code.opening);
+        assert!(code.config.validate().is_ok());
+        assert_ne!(
+            code.to_string(),
+            challenge(Date::from_days(20_745)).to_string()
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{
+    cell::CellType,
+    grid::{Grid, GridConfig, GridLoc},
+    layout::{self, Layout},
+    rating::{self, BoardRating},
+    share::ShareCode,
+    solver,
+};
+
+/// What the solver made of a hand-crafted board.
+#[derive(Clone, Copy, Debug)]
+pub struct Verdict {
+    /// Whether the board can be cleared from the cells it starts with without guessing.
+    pub solvable: bool,
+    pub rating: BoardRating,
+}
+
+/// A board being drawn by hand, shown instead of the regular game.
+///
+/// Both the mines and the cells the player starts with are shown revealed, so
+/// the numbers on the starting cells follow the mines as they are placed.
+pub struct Editor {
+    /// The board as drawn.
+    pub grid: Grid,
+    /// The board's size and rules.
+    config: GridConfig,
+    /// What the solver made of the board, until it is edited again.
+    pub verdict: Option<Result<Verdict, String>>,
+    /// Name the board is exported under.
+    pub name: String,
+    /// Where the last export went, or why it failed.
+    pub status: Option<String>,
+}
+
+impl Editor {
+    /// Starts an empty board with `config`'s size and rules.
+    pub fn new(config: &GridConfig) -> Self {
+        Self {
+            grid: config.new_grid(),
+            config: config.clone(),
+            verdict: None,
+            name: String::new(),
+            status: None,
+        }
+    }
+
+    /// Makes a safe cell one the player starts with, or hides it again.
+    pub fn toggle_revealed(&mut self, loc: GridLoc) {
+        let Some(cell) = self.grid.get_mut(loc.row, loc.col) else {
+            return;
+        };
+        if cell.is_mine() {
+            return;
+        }
+        cell.cell_type = match cell.cell_type {
+            CellType::Revealed => CellType::Hidden,
+            _ => CellType::Revealed,
+        };
+        self.edited();
+    }
+
+    /// Adds a mine to a cell, emptying it again once it is full.
+    pub fn add_mine(&mut self, loc: GridLoc) {
+        let full = self.grid.mines_per_cell();
+        let Some(cell) = self.grid.get_mut(loc.row, loc.col) else {
+            return;
+        };
+        cell.mines = (cell.mines + 1) % (full + 1);
+        cell.cell_type = if cell.is_mine() {
+            CellType::Revealed
+        } else {
+            CellType::Hidden
+        };
+        self.edited();
+    }
+
+    fn edited(&mut self) {
+        self.verdict = None;
+        self.status = None;
+    }
+
+    /// Returns the share code playing the board as drawn.
+    pub fn share_code(&self) -> Result<ShareCode, String> {
+        let layout = Layout::of(&self.grid);
+        if layout.mines() == 0 {
+            return Err("Right-click cells to place mines".to_string());
+        }
+        if layout.revealed().next().is_none() {
+            return Err("Click a cell for the player to start from".to_string());
+        }
+        ShareCode::crafted(self.config.clone(), layout)
+    }
+
+    /// Lets the solver play the board from the cells it starts with.
+    pub fn check(&mut self) {
+        self.verdict = Some(self.share_code().and_then(|code| {
+            let grid = code.generate()?;
+            Ok(Verdict {
+                solvable: solver::is_solvable(&grid),
+                rating: rating::rate(&grid),
+            })
+        }));
+    }
+
+    /// Checks that a board can be exported under `name`, which names its file.
+    pub fn validate_name(name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            Err("Name the board to export it".to_string())
+        } else if !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+        {
+            Err("Board names can only use letters, digits, spaces, - and _".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Saves the board as a board file named after it.
+    pub fn export(&mut self) {
+        let saved = Self::validate_name(&self.name)
+            .and_then(|()| self.share_code())
+            .and_then(|code| layout::save(self.name.trim(), &code).map_err(|e| e.to_string()));
+        self.status = Some(match saved {
+            Ok(path) => format!("Saved to {}", path.display()),
+            Err(error) => error,
+        });
+    }
+
+    /// Returns the share code to copy, noting whether the board has one.
+    pub fn copy_code(&mut self) -> Option<String> {
+        match self.share_code() {
+            Ok(code) => {
+                self.status = Some("Copied the board code".to_string());
+                Some(code.to_string())
+            }
+            Err(error) => {
+                self.status = Some(error);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{grid::GridSize, state::Difficulty};
+
+    #[test]
+    fn test_editor_checks_board() {
+        let config = GridConfig {
+            size: GridSize { rows: 5, cols: 5 },
+            ..Difficulty::DIFF_EASY.state.clone()
+        };
+        let mut editor = Editor::new(&config);
+        assert!(editor.share_code().is_err());
+        editor.add_mine(GridLoc { row: 4, col: 4 });
+        assert!(editor.share_code().is_err());
+
+        // The empty corner opens the whole board around the mine.
+        editor.toggle_revealed(GridLoc { row: 0, col: 0 });
+        editor.check();
+        assert!(matches!(
+            editor.verdict,
+            Some(Ok(Verdict { solvable: true, .. }))
+        ));
+
+        // A mine next to the only starting cell leaves nothing to deduce from its 1.
+        editor.add_mine(GridLoc { row: 0, col: 1 });
+        assert!(editor.verdict.is_none());
+        editor.check();
+        assert!(matches!(
+            editor.verdict,
+            Some(Ok(Verdict {
+                solvable: false,
+                ..
+            }))
+        ));
+
+        // Mines cannot start revealed, and a full cell empties on the next click.
+        editor.toggle_revealed(GridLoc { row: 0, col: 1 });
+        let code = editor.share_code().unwrap();
+        assert_eq!(code.to_string(), "5x5:o*.22*");
+        editor.add_mine(GridLoc { row: 0, col: 1 });
+        assert_eq!(editor.share_code().unwrap().config.mines, 1);
+
+        let replayed: ShareCode = code.to_string().parse().unwrap();
+        assert_eq!(
+            replayed.generate().unwrap().get(0, 1).unwrap().mines,
+            code.generate().unwrap().get(0, 1).unwrap().mines
+        );
+    }
+}
@@ -2,47 +2,129 @@ use std::time::Instant;
 
 use iced::{
     Alignment, Color, Element, Length,
-    widget::{Button, Image, button, container, row},
+    widget::{Button, Image, button, column, container, row, text, tooltip},
 };
 
-use crate::{assets::*, game::App, grid::GridConfig, message::Message, state::GameState};
+use crate::{
+    assets::*, coop::RaceStatus, game::App, grid::GridConfig, message::Message, skin::Skin,
+    state::GameState, theme::player_color,
+};
 
 pub fn header(app: &App) -> Element<'static, Message> {
-    let score_display = match &app.state {
-        GameState::Started(grid) | GameState::Won(grid) | GameState::Lost(grid) => {
+    let skin = &app.skin;
+    // Endless boards have no mine count, so they show the score instead.
+    let score_display = match (&app.endless, &app.state) {
+        (Some(board), _) => score_display(skin, board.score().min(999) as isize),
+        (None, GameState::Started(grid) | GameState::Won(grid) | GameState::Lost(grid)) => {
             let mines = grid.count_mines() as isize;
             let flags = grid.count_flags() as isize;
-            score_display(mines - flags)
+            score_display(skin, mines - flags)
         }
-        GameState::CreationScreen(GridConfig { mines, .. })
-        | GameState::Initialized(GridConfig { mines, .. }) => score_display(*mines as isize),
+        (
+            None,
+            GameState::CreationScreen(GridConfig { mines, .. })
+            | GameState::Initialized(GridConfig { mines, .. }),
+        ) => score_display(skin, *mines as isize),
     };
-    let time_display = time_display(app.started);
-    let toggle_button = button_image(get_flag_image().opacity(app.click_mode.opacity()))
-        .on_press(Message::ClickModeToggle);
+    let time_display = time_display(skin, app.started);
+    let toggle_button = tooltip(
+        button_image(get_flag_image(skin).opacity(app.click_mode.opacity()))
+            .on_press(Message::ClickModeToggle),
+        app.click_mode.to_string(),
+        tooltip::Position::Bottom,
+    );
     let hint_button = button("Hint?").on_press(match app.state {
         GameState::Started(..) => Message::RequestHint,
         _ => Message::NoOp,
     });
-    let face_button = button_image(get_face_image(app.face)).on_press(Message::GameNew);
+    let auto_button =
+        button(if app.auto_play { "Stop" } else { "Auto" }).on_press(match app.state {
+            // The solver's flags would score for nobody in a hot-seat game.
+            GameState::Initialized(..) | GameState::Started(..) if app.hot_seat.is_none() => {
+                Message::AutoToggle
+            }
+            _ => Message::NoOp,
+        });
+    let face = match (&app.endless, &app.state) {
+        (Some(board), _) if board.is_over() => Face::Dead,
+        (None, GameState::Won(..)) => Face::Cool,
+        (None, GameState::Lost(..)) => Face::Dead,
+        _ => app.face,
+    };
+    let face_button = button_image(get_face_image(skin, face)).on_press(Message::GameNew);
+    let settings_button = button("⚙").on_press(Message::SettingsToggle);
     container(
-        row![
-            // button(app.click_mode.to_string()).on_press(Message::ClickModeToggle),
-            toggle_button,
-            hint_button,
-            score_display,
-            face_button,
-            time_display,
+        column![
+            row![
+                toggle_button,
+                hint_button,
+                auto_button,
+                score_display,
+                face_button,
+                time_display,
+                settings_button,
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            race_display(app),
+            hot_seat_display(app),
         ]
-        .spacing(10)
-        .align_y(Alignment::Center),
+        .spacing(5)
+        .align_x(Alignment::Center),
     )
     .align_x(Alignment::Center)
     .width(Length::Fill)
     .into()
 }
 
-fn score_display(score: isize) -> Element<'static, Message> {
+/// Shows how far the other racers got, in their colors, during a race.
+fn race_display(app: &App) -> Option<Element<'static, Message>> {
+    let coop = app.coop.as_ref().filter(|coop| coop.race)?;
+    let mut racers: Vec<_> = coop
+        .progress
+        .iter()
+        .filter(|&(&player, _)| player != coop.player)
+        .collect();
+    if racers.is_empty() {
+        return None;
+    }
+    racers.sort_by_key(|&(&player, _)| player);
+    let racers = racers.into_iter().map(|(&player, progress)| {
+        let status = match progress.status {
+            _ if coop.winner == Some(player) => " 🏆",
+            RaceStatus::Playing => "",
+            RaceStatus::Won => " ✔",
+            RaceStatus::Lost => " 💥",
+        };
+        text(format!("P{} {}%{status}", player + 1, progress.cleared))
+            .color(player_color(player))
+            .into()
+    });
+    Some(row(racers).spacing(15).into())
+}
+
+/// Shows the scores of a hot-seat game, pointing at the player whose turn it is.
+fn hot_seat_display(app: &App) -> Option<Element<'static, Message>> {
+    let hot_seat = app.hot_seat.as_ref()?;
+    let playing = matches!(
+        app.state,
+        GameState::Initialized(..) | GameState::Started(..)
+    );
+    let players = hot_seat.scores.iter().enumerate().map(|(player, score)| {
+        let player = player as u8;
+        let turn = if playing && hot_seat.turn == player {
+            "▶ "
+        } else {
+            ""
+        };
+        text(format!("{turn}Player {}: {score}", player + 1))
+            .color(player_color(player))
+            .into()
+    });
+    Some(row(players).spacing(15).into())
+}
+
+fn score_display(skin: &Skin, score: isize) -> Element<'static, Message> {
     let abs_score = score.unsigned_abs();
     let digits = [
         (abs_score / 100) % 10,
@@ -52,22 +134,22 @@ fn score_display(score: isize) -> Element<'static, Message> {
 
     let row = if score < 0 {
         row![
-            get_minus_image(),
-            get_clock_image(digits[1]),
-            get_clock_image(digits[2])
+            get_minus_image(skin),
+            get_clock_image(skin, digits[1]),
+            get_clock_image(skin, digits[2])
         ]
     } else {
         row![
-            get_clock_image(digits[0]),
-            get_clock_image(digits[1]),
-            get_clock_image(digits[2])
+            get_clock_image(skin, digits[0]),
+            get_clock_image(skin, digits[1]),
+            get_clock_image(skin, digits[2])
         ]
     };
 
     row.into()
 }
 
-fn time_display(started: Option<Instant>) -> Element<'static, Message> {
+fn time_display(skin: &Skin, started: Option<Instant>) -> Element<'static, Message> {
     let seconds = match started {
         Some(started) => started.elapsed().as_secs() as usize,
         None => 0,
@@ -75,9 +157,9 @@ fn time_display(started: Option<Instant>) -> Element<'static, Message> {
 
     let digits = [(seconds / 100) % 10, (seconds / 10) % 10, seconds % 10];
     row![
-        get_clock_image(digits[0]).width(23),
-        get_clock_image(digits[1]).width(23),
-        get_clock_image(digits[2]).width(23)
+        get_clock_image(skin, digits[0]).width(23),
+        get_clock_image(skin, digits[1]).width(23),
+        get_clock_image(skin, digits[2]).width(23)
     ]
     .into()
 }
@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+
+use rand::{SeedableRng, seq::SliceRandom};
+use rand_chacha::ChaCha20Rng;
+
+use crate::{
+    cell::{Cell, CellType},
+    grid::{CellFlagResult, CellRevealResult, GridConfig, GridLoc, GridSize, next_revision},
+    topology::{Neighborhood, Topology},
+};
+
+/// Rows and columns of a chunk, the unit in which the endless board is generated.
+const CHUNK_SIZE: i64 = 16;
+
+/// Lowest share of mines in an endless board.
+///
+/// Sparser boards have openings that never end, so a single click could keep
+/// generating chunks forever.
+const MIN_DENSITY: f32 = 0.15;
+
+/// A cell on the endless board, which extends in every direction from `(0, 0)`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct WorldLoc {
+    pub row: i64,
+    pub col: i64,
+}
+
+impl WorldLoc {
+    /// Returns the 8 cells around this one.
+    fn neighbors(self) -> impl Iterator<Item = WorldLoc> {
+        (-1..=1)
+            .flat_map(|rows| (-1..=1).map(move |cols| (rows, cols)))
+            .filter(|&offset| offset != (0, 0))
+            .map(move |(rows, cols)| WorldLoc {
+                row: self.row + rows,
+                col: self.col + cols,
+            })
+    }
+
+    /// Returns the chunk holding this cell and the cell's index inside it.
+    fn chunk(self) -> (ChunkKey, usize) {
+        let key = ChunkKey {
+            row: self.row.div_euclid(CHUNK_SIZE),
+            col: self.col.div_euclid(CHUNK_SIZE),
+        };
+        let index = self.row.rem_euclid(CHUNK_SIZE) * CHUNK_SIZE + self.col.rem_euclid(CHUNK_SIZE);
+        (key, index as usize)
+    }
+}
+
+/// Position of a chunk, counted in chunks from the one holding `(0, 0)`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct ChunkKey {
+    row: i64,
+    col: i64,
+}
+
+impl ChunkKey {
+    /// Mixes the board's seed with the chunk's position, so every chunk gets its own mines.
+    fn seed(self, seed: u64) -> u64 {
+        seed ^ (self.row as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+            ^ (self.col as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f)
+    }
+}
+
+/// An endless minesweeper board, generated lazily one chunk at a time.
+///
+/// Every chunk holds the same number of mines, placed from the seed and the
+/// chunk's position, so the same seed and first click always produce the same
+/// world. Only chunks the player has reached are kept in memory. The score is
+/// the number of safe cells revealed, and the game ends on the first mine.
+#[derive(Debug)]
+pub struct EndlessBoard {
+    seed: u64,
+    mines_per_chunk: usize,
+    /// The first reveal, whose neighbors are kept free of mines.
+    start: Option<WorldLoc>,
+    chunks: HashMap<ChunkKey, Vec<Cell>>,
+    /// Top-left cell of the viewport.
+    origin: WorldLoc,
+    score: usize,
+    lost: bool,
+    /// Changes whenever the cells in view may have, so drawings of the board know when to redo.
+    revision: u64,
+}
+
+impl EndlessBoard {
+    /// Rows and columns of cells shown at once.
+    pub const VIEW: GridSize = GridSize { rows: 16, cols: 16 };
+
+    /// Returns whether an endless board can follow `config`'s rules: endless
+    /// boards are square, never wrap, count the standard neighbors and hold a
+    /// single mine per cell.
+    pub fn supports(config: &GridConfig) -> bool {
+        config.topology == Topology::Square
+            && !config.wrap
+            && config.neighborhood == Neighborhood::Standard
+            && config.mines_per_cell == 1
+    }
+
+    /// Creates a board with roughly `density` of its cells mined, at least `MIN_DENSITY`.
+    pub fn new(seed: u64, density: f32) -> Self {
+        let cells = (CHUNK_SIZE * CHUNK_SIZE) as f32;
+        Self {
+            seed,
+            mines_per_chunk: (density.clamp(MIN_DENSITY, 1.0) * cells).round() as usize,
+            start: None,
+            chunks: HashMap::new(),
+            origin: WorldLoc {
+                row: -(Self::VIEW.rows as i64) / 2,
+                col: -(Self::VIEW.cols as i64) / 2,
+            },
+            score: 0,
+            lost: false,
+            revision: next_revision(),
+        }
+    }
+
+    /// Returns the cell of the world shown at `loc` in the viewport.
+    pub fn world(&self, loc: GridLoc) -> WorldLoc {
+        WorldLoc {
+            row: self.origin.row + loc.row as i64,
+            col: self.origin.col + loc.col as i64,
+        }
+    }
+
+    /// Moves the viewport by the given number of rows and columns.
+    pub fn pan(&mut self, rows: i64, cols: i64) {
+        self.origin.row += rows;
+        self.origin.col += cols;
+        self.revision = next_revision();
+    }
+
+    /// Returns a number that changes whenever the cells in view may have changed.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Returns the number of safe cells revealed so far.
+    pub fn score(&self) -> usize {
+        self.score
+    }
+
+    /// Returns `true` once a mine has been revealed.
+    pub fn is_over(&self) -> bool {
+        self.lost
+    }
+
+    /// Returns the cell at `loc`, or `None` if its chunk has not been generated yet.
+    pub fn get(&self, loc: WorldLoc) -> Option<&Cell> {
+        let (key, index) = loc.chunk();
+        self.chunks.get(&key).map(|cells| &cells[index])
+    }
+
+    /// Returns the cell at `loc`, generating its chunk if needed.
+    fn cell_mut(&mut self, loc: WorldLoc) -> &mut Cell {
+        let (key, index) = loc.chunk();
+        if !self.chunks.contains_key(&key) {
+            let cells = self.generate(key);
+            self.chunks.insert(key, cells);
+        }
+        &mut self.chunks.get_mut(&key).unwrap()[index]
+    }
+
+    /// Places the mines of a chunk, keeping the first reveal and its neighbors free.
+    fn generate(&self, key: ChunkKey) -> Vec<Cell> {
+        let mut rng = ChaCha20Rng::seed_from_u64(key.seed(self.seed));
+        let loc = |index: usize| WorldLoc {
+            row: key.row * CHUNK_SIZE + index as i64 / CHUNK_SIZE,
+            col: key.col * CHUNK_SIZE + index as i64 % CHUNK_SIZE,
+        };
+        let is_safe = |loc: WorldLoc| {
+            self.start.is_some_and(|start| {
+                start.row.abs_diff(loc.row) <= 1 && start.col.abs_diff(loc.col) <= 1
+            })
+        };
+        let mut cells = vec![Cell::default(); (CHUNK_SIZE * CHUNK_SIZE) as usize];
+        let mut candidates: Vec<usize> = (0..cells.len()).filter(|&i| !is_safe(loc(i))).collect();
+        let amount = self.mines_per_chunk.min(candidates.len());
+        let (chosen, _) = candidates.partial_shuffle(&mut rng, amount);
+        for &mut index in chosen {
+            cells[index].mines = 1;
+        }
+        cells
+    }
+
+    /// Returns the number of mines around `loc`.
+    ///
+    /// Chunks that were never generated count as empty, which is only accurate
+    /// around revealed cells: revealing a cell generates every chunk it touches.
+    pub fn count_neighboring_mines(&self, loc: WorldLoc) -> u8 {
+        loc.neighbors()
+            .filter_map(|n| self.get(n))
+            .map(|cell| cell.mines)
+            .sum()
+    }
+
+    fn count_neighboring_flags(&self, loc: WorldLoc) -> u8 {
+        loc.neighbors()
+            .filter_map(|n| self.get(n))
+            .filter(|cell| matches!(cell.cell_type, CellType::Flagged(..)))
+            .count() as u8
+    }
+
+    /// Reveals a cell, cascading through empty cells across chunk boundaries.
+    ///
+    /// The first reveal decides where the board starts, so it is always an opening.
+    pub fn reveal(&mut self, loc: WorldLoc) -> CellRevealResult {
+        if self.lost {
+            return CellRevealResult::AlreadyRevealed;
+        }
+        self.revision = next_revision();
+        self.start.get_or_insert(loc);
+        match self.cell_mut(loc).cell_type {
+            CellType::Revealed => return CellRevealResult::AlreadyRevealed,
+            CellType::Flagged(..) => return CellRevealResult::Flagged,
+            CellType::Hidden => {}
+        }
+
+        let mut stack = vec![loc];
+        let mut revealed = 0;
+        while let Some(current) = stack.pop() {
+            for neighbor in current.neighbors() {
+                self.cell_mut(neighbor);
+            }
+            let cell = self.cell_mut(current);
+            if !matches!(cell.cell_type, CellType::Hidden) {
+                continue;
+            }
+            cell.cell_type = CellType::Revealed;
+            if cell.is_mine() {
+                self.lose();
+                return CellRevealResult::Mine;
+            }
+            revealed += 1;
+            if self.count_neighboring_mines(current) == 0 {
+                stack.extend(
+                    current
+                        .neighbors()
+                        .filter(|&n| matches!(self.get(n).unwrap().cell_type, CellType::Hidden)),
+                );
+            }
+        }
+        self.score += revealed;
+        CellRevealResult::Success
+    }
+
+    /// Reveals every unflagged neighbor of a revealed cell whose mines are all flagged.
+    pub fn chord(&mut self, loc: WorldLoc) -> CellRevealResult {
+        match self.get(loc).map(|cell| &cell.cell_type) {
+            Some(CellType::Revealed) => {}
+            _ => return CellRevealResult::Success,
+        }
+        if self.count_neighboring_flags(loc) != self.count_neighboring_mines(loc) {
+            return CellRevealResult::Success;
+        }
+        for neighbor in loc.neighbors() {
+            if matches!(self.reveal(neighbor), CellRevealResult::Mine) {
+                return CellRevealResult::Mine;
+            }
+        }
+        CellRevealResult::Success
+    }
+
+    /// Toggles the flag on a hidden cell. Nothing can be flagged before the first reveal.
+    pub fn flag(&mut self, loc: WorldLoc) -> CellFlagResult {
+        if self.start.is_none() || self.lost {
+            return CellFlagResult::Success;
+        }
+        self.revision = next_revision();
+        let cell = self.cell_mut(loc);
+        cell.cell_type = match cell.cell_type {
+            CellType::Hidden => CellType::Flagged(1),
+            CellType::Flagged(..) => CellType::Hidden,
+            CellType::Revealed => return CellFlagResult::AlreadyRevealed,
+        };
+        CellFlagResult::Success
+    }
+
+    /// Ends the game, revealing every generated mine that was not flagged.
+    fn lose(&mut self) {
+        self.lost = true;
+        for cell in self.chunks.values_mut().flatten() {
+            if cell.is_mine() && matches!(cell.cell_type, CellType::Hidden) {
+                cell.cell_type = CellType::Revealed;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: WorldLoc = WorldLoc { row: 0, col: 0 };
+
+    #[test]
+    fn test_endless_cascade_crosses_chunks() {
+        let mut board = EndlessBoard::new(6767, 0.2);
+        assert!(matches!(board.reveal(START), CellRevealResult::Success));
+        // The first reveal is an opening on the corner of four chunks.
+        let corner = WorldLoc { row: -1, col: -1 };
+        assert!(matches!(
+            board.get(corner).unwrap().cell_type,
+            CellType::Revealed
+        ));
+        assert!(board.chunks.len() >= 4);
+        let revealed = board
+            .chunks
+            .values()
+            .flatten()
+            .filter(|cell| matches!(cell.cell_type, CellType::Revealed))
+            .count();
+        assert_eq!(board.score(), revealed);
+    }
+
+    #[test]
+    fn test_endless_is_reproducible() {
+        let far = WorldLoc {
+            row: -100,
+            col: 250,
+        };
+        let mines = |board: &mut EndlessBoard| {
+            board.reveal(START);
+            board.cell_mut(far);
+            let (key, _) = far.chunk();
+            let cells = &board.chunks[&key];
+            cells.iter().map(|cell| cell.mines).collect::<Vec<_>>()
+        };
+        let first = mines(&mut EndlessBoard::new(42, 0.2));
+        assert_eq!(first, mines(&mut EndlessBoard::new(42, 0.2)));
+        assert_ne!(first, mines(&mut EndlessBoard::new(43, 0.2)));
+        assert_eq!(first.iter().filter(|&&mines| mines > 0).count(), 51);
+    }
+
+    #[test]
+    fn test_endless_mine_ends_game() {
+        let mut board = EndlessBoard::new(1, 0.3);
+        board.reveal(START);
+        let score = board.score();
+        let mine = (0..CHUNK_SIZE)
+            .map(|col| WorldLoc { row: 5, col })
+            .find(|&loc| board.get(loc).unwrap().is_mine())
+            .unwrap();
+        assert!(matches!(board.reveal(mine), CellRevealResult::Mine));
+        assert!(board.is_over());
+        assert_eq!(board.score(), score);
+    }
+}
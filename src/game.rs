@@ -1,20 +1,50 @@
-use std::time::Instant;
+use std::{
+    net::SocketAddr,
+    ops::RangeInclusive,
+    sync::{Arc, mpsc},
+    thread,
+    time::{Duration, Instant},
+};
 
 use crate::algorithms;
 use crate::{
+    animation::{self, BoardAnimation, Effect},
     assets::Face,
-    cell::Cell,
+    board::{BoardCanvas, BoardCells, BoardInput},
+    bot::{self, BotCommand, Response},
+    cli::{LaunchOptions, LaunchState},
+    coop::{self, Coop, CoopEvent, CoopMessage, CoopRole, Progress},
+    daily::{self, DailyHistory, Date},
+    editor::{Editor, Verdict},
     elements::header,
-    grid::{CellChordResult, CellRevealResult, Grid, GridConfig, GridLoc},
+    endless::EndlessBoard,
+    grid::{CellChordResult, CellRevealResult, Grid, GridConfig, GridLoc, GridSize, SafeZone},
+    hotseat::HotSeat,
     message::Message,
+    presets::Presets,
+    rating::{self, BoardRating, Rating},
+    settings::{ChordStyle, Settings},
+    share::ShareCode,
+    skin::{self, Skin},
+    solver::{self, Deduction},
     state::{Difficulty, GameState},
     theme::*,
+    topology::{Neighborhood, Topology},
 };
+use iced::futures::channel::oneshot;
 use iced::time::{self, seconds};
-use iced::{Alignment, Background, Border, Color, Element, Length, Task, window};
+use iced::{
+    Alignment, Background, Border, Element, Length, Padding, Point, Size, Task, Theme, clipboard,
+    keyboard, window,
+};
 use iced::{
     Subscription,
-    widget::{button, column, container, grid as iced_grid, row, stack, text},
+    widget::{
+        button, canvas, checkbox, column, container, mouse_area, pick_list, responsive, row,
+        scrollable,
+        scrollable::{Direction, Scrollbar},
+        stack, text, text_input, tooltip,
+    },
 };
 use iced_aw::number_input;
 
@@ -50,14 +80,104 @@ impl ClickMode {
             ClickMode::Flag => "Flag Mode",
         }
     }
+
+    /// Turns a click on a cell into the move it makes in this mode.
+    pub fn apply(self, message: Message) -> Message {
+        match self {
+            ClickMode::Reveal => message,
+            ClickMode::Flag => match message {
+                RevealClick(loc) => FlagClick(loc),
+                FlagClick(loc) => RevealClick(loc),
+                ChordClick(loc) => ChordClick(loc),
+                _ => unreachable!("Unhandled message: {:?}", message),
+            },
+        }
+    }
 }
 
+/// Rows and columns of pixels in a cell sprite.
+const SPRITE_SIZE: f32 = 16.0;
+/// Most sprite sizes the zoom moves away from the size fitting the window.
+const MAX_ZOOM: i32 = 6;
+/// Room the window needs around the board for the header and padding.
+const WINDOW_MARGIN: Size = Size::new(80.0, 210.0);
+/// Largest window resizing around the board opens.
+const MAX_WINDOW: Size = Size::new(1280.0, 960.0);
+
 pub struct App {
     pub state: GameState,
     pub click_mode: ClickMode,
+    pub safe_zone: SafeZone,
     pub face: Face,
     pub now: Instant,
     pub started: Option<Instant>,
+    /// Seed for the next board, consumed by its first click.
+    pub seed: Option<u64>,
+    pub no_guess: bool,
+    /// Only generate boards with this rating.
+    pub rating_filter: Option<Rating>,
+    /// Rating of the board currently being played.
+    pub board_rating: Option<BoardRating>,
+    pub scale: f32,
+    /// Share code of the board currently being played.
+    pub share_code: Option<ShareCode>,
+    /// Share code of the board being generated in the background, which the game waits for.
+    pub generating: Option<ShareCode>,
+    /// Board whose generation `update` has yet to start.
+    pub queued_board: Option<ShareCode>,
+    /// Why the last board could not be generated.
+    pub generate_error: Option<String>,
+    /// A bot waiting for the board its first reveal started.
+    pub bot_reply: Option<mpsc::SyncSender<Response>>,
+    /// Address where bots can play the game shown in the window.
+    pub bot_listen: Option<SocketAddr>,
+    /// Whether the solver is currently playing the game.
+    pub auto_play: bool,
+    /// Milliseconds between two moves of the solver.
+    pub auto_delay: u64,
+    /// Whether the solver may guess when nothing can be deduced.
+    pub auto_guess: bool,
+    /// The endless board being played, shown instead of the regular game.
+    pub endless: Option<EndlessBoard>,
+    /// The board being drawn in the editor, shown instead of the regular game.
+    pub editor: Option<Editor>,
+    /// Last pointer position over the endless board, in cells.
+    pub pointer: Point,
+    /// Where the pointer was when the endless board last moved under a drag, in cells.
+    pub drag_anchor: Option<Point>,
+    /// Day of the daily challenge being played, if the board is one.
+    pub daily: Option<Date>,
+    pub daily_history: DailyHistory,
+    /// Whether the creation screen shows past daily challenges instead.
+    pub show_daily_history: bool,
+    /// The co-op game this window hosts or joined.
+    pub coop: Option<Coop>,
+    /// Turns and scores of a hot-seat game for two players.
+    pub hot_seat: Option<HotSeat>,
+    /// Names of the installed skins.
+    pub skins: Vec<String>,
+    /// The sprites and colors the game is drawn with.
+    pub skin: Arc<Skin>,
+    /// Why the chosen skin could not be loaded.
+    pub skin_error: Option<String>,
+    pub settings: Settings,
+    /// Whether the settings screen is shown over the board.
+    pub show_settings: bool,
+    pub presets: Presets,
+    /// Name typed in to save the creation screen's board as a preset.
+    pub preset_name: String,
+    /// Why the board could not be saved as a preset.
+    pub preset_error: Option<String>,
+    /// Preset of the game being played, whose result goes into the preset's stats.
+    pub preset: Option<String>,
+    /// Whether auto play or a bot made a move in the game being played, which
+    /// keeps it out of the stats and the daily history.
+    pub assisted: bool,
+    /// Share of cells holding a mine the player asked for, as a fraction of the
+    /// cell count, which the mine count follows as the board is resized.
+    pub density: Option<f32>,
+    /// The effect playing on the board after the last move.
+    pub animation: Option<BoardAnimation>,
 }
 
 impl Default for App {
@@ -65,27 +185,798 @@ impl Default for App {
         Self {
             state: GameState::default(),
             click_mode: ClickMode::default(),
+            safe_zone: SafeZone::default(),
             face: Face::default(),
             now: Instant::now(),
             started: None,
+            seed: None,
+            no_guess: false,
+            rating_filter: None,
+            board_rating: None,
+            scale: 1.0,
+            share_code: None,
+            generating: None,
+            queued_board: None,
+            generate_error: None,
+            bot_reply: None,
+            bot_listen: None,
+            auto_play: false,
+            auto_delay: 250,
+            auto_guess: false,
+            endless: None,
+            editor: None,
+            pointer: Point::ORIGIN,
+            drag_anchor: None,
+            daily: None,
+            daily_history: DailyHistory::default(),
+            show_daily_history: false,
+            coop: None,
+            hot_seat: None,
+            skins: Vec::new(),
+            skin: Arc::new(Skin::builtin()),
+            skin_error: None,
+            settings: Settings::default(),
+            show_settings: false,
+            presets: Presets::default(),
+            preset_name: String::new(),
+            preset_error: None,
+            preset: None,
+            assisted: false,
+            density: None,
+            animation: None,
         }
     }
 }
 
 impl App {
+    /// Allowed range for the delay between automatic moves, in milliseconds.
+    pub const AUTO_DELAY_RANGE: RangeInclusive<u64> = 10..=2000;
+
+    pub fn new(options: &LaunchOptions) -> (Self, Task<Message>) {
+        let settings = Settings::load();
+        let (skin, skin_error) = skin::select(&settings.skin);
+        let mut app = Self {
+            seed: options.seed,
+            no_guess: options.no_guess || settings.no_guess,
+            safe_zone: if settings.safe_opening {
+                SafeZone::Opening
+            } else {
+                SafeZone::Cell
+            },
+            rating_filter: options.rating,
+            scale: options.scale,
+            bot_listen: options.bot_listen,
+            daily_history: DailyHistory::load(),
+            presets: Presets::load(),
+            skins: skin::available(),
+            skin: Arc::new(skin),
+            skin_error,
+            settings,
+            coop: options.coop.map(|role| Coop::new(role, options.race)),
+            ..Self::default()
+        };
+        app.state = match &options.state {
+            LaunchState::Config(config) => Initialized(config.clone()),
+            LaunchState::Code(code) => app.start_game(code.clone()),
+        };
+        let task = app.generate_queued();
+        (app, task)
+    }
+
+    pub fn scale_factor(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.settings.color_scheme.theme()
+    }
+
+    /// Starts generating the board described by `code`, showing an empty board
+    /// until it is ready.
+    fn start_game(&mut self, code: ShareCode) -> GameState {
+        self.safe_zone = if code.opening {
+            SafeZone::Opening
+        } else {
+            SafeZone::Cell
+        };
+        self.no_guess = code.no_guess;
+        self.rating_filter = code.rating;
+        self.generate_error = None;
+        self.assisted = false;
+        let config = code.config.clone();
+        self.generating = Some(code.clone());
+        self.queued_board = Some(code);
+        Initialized(config)
+    }
+
+    /// Leaves the current game and any mode it was played in, before a new one is set up.
+    fn leave_game(&mut self) {
+        self.auto_play = false;
+        self.assisted = false;
+        self.endless = None;
+        self.editor = None;
+        self.generating = None;
+        self.generate_error = None;
+        self.daily = None;
+        self.hot_seat = None;
+        self.preset = None;
+        self.animation = None;
+    }
+
+    /// Starts the clock on the board generated for `code`, unless another board
+    /// was asked for in the meantime.
+    fn board_generated(
+        &mut self,
+        code: ShareCode,
+        board: Result<(Grid, BoardRating), String>,
+        state: GameState,
+    ) -> GameState {
+        if self.generating.as_ref() != Some(&code) {
+            return state;
+        }
+        self.generating = None;
+        match board {
+            Ok((grid, board_rating)) => {
+                self.board_rating = Some(board_rating);
+                self.share_code = Some(code);
+                self.started = Some(self.now);
+                GameState::resolve(grid, false)
+            }
+            Err(error) => {
+                self.generate_error = Some(error);
+                state
+            }
+        }
+    }
+
+    /// Generates the queued board on another thread, as boards with a no-guess
+    /// or rating filter can take a while to find.
+    fn generate_queued(&mut self) -> Task<Message> {
+        let Some(code) = self.queued_board.take() else {
+            return Task::none();
+        };
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            let _ = sender.send(Self::generate_board(code));
+        });
+        Task::perform(receiver, |message| message.unwrap_or(NoOp))
+    }
+
+    fn generate_board(code: ShareCode) -> Message {
+        let board = code.generate().map(|grid| {
+            let board_rating = rating::rate(&grid);
+            (grid, board_rating)
+        });
+        BoardGenerated(code, board)
+    }
+
     pub fn subscription(&self) -> Subscription<Message> {
-        let is_animating = matches!(&self.state, Started(grid) if grid.is_animating(self.now));
-        if is_animating {
+        let is_animating = matches!(&self.state, Started(grid) if grid.is_animating(self.now))
+            || self.is_animating();
+        let endless_playing = self.endless.as_ref().is_some_and(|board| !board.is_over());
+        let ticks = if is_animating {
             window::frames().map(|_| Message::NoOp)
         } else {
             match self.state {
                 Started(..) => time::every(seconds(1)).map(|_| Message::NoOp),
+                _ if endless_playing => time::every(seconds(1)).map(|_| Message::NoOp),
                 _ => Subscription::none(),
             }
+        };
+        let endless_keys = match self.endless {
+            Some(..) => keyboard::listen().filter_map(Self::endless_key),
+            None => Subscription::none(),
+        };
+        let zoom_keys = keyboard::listen().filter_map(Self::zoom_key);
+        let skip_keys = if self.is_animating() {
+            keyboard::listen().filter_map(Self::skip_key)
+        } else {
+            Subscription::none()
+        };
+        let auto_steps = match self.state {
+            Initialized(..) | Started(..) if self.auto_play => {
+                time::every(Duration::from_millis(self.auto_delay)).map(|_| AutoStep)
+            }
+            _ => Subscription::none(),
+        };
+        let bot_requests = match self.bot_listen {
+            Some(addr) => Subscription::run_with(addr, bot::window_requests),
+            None => Subscription::none(),
+        };
+        let coop_events = match self.coop.as_ref().map(|coop| coop.role) {
+            Some(CoopRole::Host(addr)) => Subscription::run_with(addr, coop::host),
+            Some(CoopRole::Join(addr)) => Subscription::run_with(addr, coop::join),
+            None => Subscription::none(),
+        };
+        Subscription::batch([
+            ticks,
+            auto_steps,
+            bot_requests,
+            endless_keys,
+            zoom_keys,
+            skip_keys,
+            coop_events,
+        ])
+    }
+
+    /// Pans the endless board with the arrow keys or WASD.
+    fn endless_key(event: keyboard::Event) -> Option<Message> {
+        use keyboard::{Key, key::Named};
+
+        let keyboard::Event::KeyPressed { key, .. } = event else {
+            return None;
+        };
+        let (rows, cols) = match key.as_ref() {
+            Key::Named(Named::ArrowUp) | Key::Character("w") => (-1, 0),
+            Key::Named(Named::ArrowDown) | Key::Character("s") => (1, 0),
+            Key::Named(Named::ArrowLeft) | Key::Character("a") => (0, -1),
+            Key::Named(Named::ArrowRight) | Key::Character("d") => (0, 1),
+            _ => return None,
+        };
+        Some(EndlessPan(rows, cols))
+    }
+
+    /// Zooms with Ctrl and `+`, `-` or `0`, like in browsers.
+    fn zoom_key(event: keyboard::Event) -> Option<Message> {
+        let keyboard::Event::KeyPressed { key, modifiers, .. } = event else {
+            return None;
+        };
+        if !modifiers.command() {
+            return None;
+        }
+        match key.as_ref() {
+            keyboard::Key::Character("+" | "=") => Some(ZoomIn),
+            keyboard::Key::Character("-") => Some(ZoomOut),
+            keyboard::Key::Character("0") => Some(ZoomFit),
+            _ => None,
+        }
+    }
+
+    /// Skips the animation playing on the board with Escape.
+    fn skip_key(event: keyboard::Event) -> Option<Message> {
+        use keyboard::{Key, key::Named};
+
+        match event {
+            keyboard::Event::KeyPressed {
+                key: Key::Named(Named::Escape),
+                ..
+            } => Some(AnimationSkip),
+            _ => None,
+        }
+    }
+
+    fn is_animating(&self) -> bool {
+        self.animation
+            .as_ref()
+            .is_some_and(|animation| animation.is_running(self.now))
+    }
+
+    /// Starts the effect following a move at `origin`, given which cells were
+    /// revealed before it. Any effect still playing stops.
+    fn animate(&mut self, origin: GridLoc, before: Option<&[bool]>) {
+        self.animation = None;
+        if self.settings.reduce_motion || self.endless.is_some() {
+            return;
+        }
+        let (effect, grid) = match &self.state {
+            Started(grid) => (Effect::Ripple, grid),
+            Lost(grid) => (Effect::Explosion, grid),
+            Won(grid) => (Effect::Sweep, grid),
+            CreationScreen(..) | Initialized(..) => return,
+        };
+        self.animation = BoardAnimation::new(effect, grid, before, origin, self.now);
+    }
+
+    /// Returns the size of the board being shown, in cells.
+    fn board_size(&self) -> (Topology, GridSize) {
+        if self.endless.is_some() {
+            return (Topology::Square, EndlessBoard::VIEW);
+        }
+        if let Some(editor) = &self.editor {
+            let grid = &editor.grid;
+            return (
+                grid.topology(),
+                GridSize {
+                    rows: grid.rows(),
+                    cols: grid.cols(),
+                },
+            );
+        }
+        match &self.state {
+            CreationScreen(config) | Initialized(config) => (config.topology, config.size),
+            Started(grid) | Won(grid) | Lost(grid) => (
+                grid.topology(),
+                GridSize {
+                    rows: grid.rows(),
+                    cols: grid.cols(),
+                },
+            ),
+        }
+    }
+
+    /// Returns the side of a cell in pixels, fitting the board in `space` and then zooming.
+    ///
+    /// Cells are whole multiples of the sprites' size so that pixels stay crisp.
+    /// Only boards too large for the window at the sprites' own size are shrunk further.
+    fn cell_side(zoom: i32, topology: Topology, size: GridSize, space: Size) -> f32 {
+        let width = match topology {
+            Topology::Square => size.cols as f32,
+            Topology::Hex => size.cols as f32 + 0.5,
+        };
+        let fit = (space.width / width).min(space.height / size.rows as f32);
+        match (fit / SPRITE_SIZE).floor() as i32 + zoom {
+            scale if scale >= 1 => SPRITE_SIZE * scale as f32,
+            _ => fit.min(SPRITE_SIZE),
+        }
+    }
+
+    /// Resizes the window to show the board with the largest crisp cells that
+    /// keep the window under `MAX_WINDOW`.
+    fn fit_window(&mut self) -> Task<Message> {
+        let (topology, size) = self.board_size();
+        let width = match topology {
+            Topology::Square => size.cols as f32,
+            Topology::Hex => size.cols as f32 + 0.5,
+        };
+        let window = |side: f32| {
+            Size::new(
+                width * side + WINDOW_MARGIN.width,
+                size.rows as f32 * side + WINDOW_MARGIN.height,
+            ) * self.scale
+        };
+        let window = (1..=4)
+            .rev()
+            .map(|scale| window(SPRITE_SIZE * scale as f32))
+            .find(|window| window.width <= MAX_WINDOW.width && window.height <= MAX_WINDOW.height)
+            .unwrap_or_else(|| window(SPRITE_SIZE));
+        self.settings.zoom = 0;
+        self.settings.save();
+        window::latest().and_then(move |id| window::resize(id, window))
+    }
+
+    /// Plays a message on the endless board, returning `false` if it is not about the board.
+    ///
+    /// Clicks carry their position in the viewport, which is translated to the world.
+    fn update_endless(&mut self, message: &Message) -> bool {
+        let Some(board) = self.endless.as_mut() else {
+            return false;
+        };
+        match *message {
+            RevealClick(loc) => {
+                self.face = Face::Surprised;
+                self.started.get_or_insert(self.now);
+                board.reveal(board.world(loc));
+            }
+            ChordClick(loc) => {
+                self.face = Face::Surprised;
+                board.chord(board.world(loc));
+            }
+            FlagClick(loc) => {
+                board.flag(board.world(loc));
+            }
+            EndlessPan(rows, cols) => board.pan(rows, cols),
+            EndlessDragStart => self.drag_anchor = Some(self.pointer),
+            EndlessDragEnd => self.drag_anchor = None,
+            EndlessDrag(point) => {
+                self.pointer = point;
+                // Pan by whole cells, keeping the part of a cell not moved yet for later.
+                if let Some(anchor) = self.drag_anchor.as_mut() {
+                    let rows = (point.y - anchor.y).trunc();
+                    let cols = (point.x - anchor.x).trunc();
+                    board.pan(-rows as i64, -cols as i64);
+                    anchor.y += rows;
+                    anchor.x += cols;
+                }
+            }
+            _ => return false,
         }
+        true
     }
+
+    /// Plays a message on the board editor, returning `false` if it is not about the editor.
+    ///
+    /// Reveal clicks pick the cells the player starts with and flag clicks add mines.
+    fn update_editor(&mut self, message: &Message) -> bool {
+        let Some(editor) = self.editor.as_mut() else {
+            return false;
+        };
+        match message {
+            RevealClick(loc) => editor.toggle_revealed(*loc),
+            FlagClick(loc) => editor.add_mine(*loc),
+            EditorCheck => editor.check(),
+            InputEditorName(name) => {
+                editor.name = name.clone();
+                editor.status = None;
+            }
+            EditorExport => editor.export(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Plays the solver's next move through the same messages as a click.
+    ///
+    /// The first move opens the center of the board. Auto play stops once the
+    /// game is over or the solver has nothing left to play.
+    fn auto_step(&mut self, now: Instant) -> Task<Message> {
+        let next = match &self.state {
+            Initialized(GridConfig { size, .. }) => Some(RevealClick(GridLoc {
+                row: size.rows / 2,
+                col: size.cols / 2,
+            })),
+            Started(grid) => {
+                solver::next_move(grid, self.auto_guess).map(|deduction| match deduction {
+                    Deduction::Safe(loc) => RevealClick(loc),
+                    Deduction::Mine(loc) => FlagAll(loc),
+                })
+            }
+            _ => None,
+        };
+        let task = match next {
+            Some(message) => {
+                let task = self.update(message, now);
+                self.face = Face::Happy;
+                self.assisted = true;
+                task
+            }
+            None => {
+                self.auto_play = false;
+                Task::none()
+            }
+        };
+        if !matches!(self.state, Initialized(..) | Started(..)) {
+            self.auto_play = false;
+        }
+        task
+    }
+
+    /// Plays a bot's request on the displayed game through the same messages as a click.
+    ///
+    /// A first reveal is answered once its board has been generated.
+    fn handle_bot(
+        &mut self,
+        BotCommand { request, reply }: BotCommand,
+        now: Instant,
+    ) -> Task<Message> {
+        let response = match (&request, request.to_message(&self.state)) {
+            (_, Err(error)) => Response::error(error),
+            // Boards dealt in the window are replayed from share codes, which cannot hold a region.
+            (bot::Request::New(new_game), _) if !new_game.safe_region.is_empty() => {
+                Response::error("safe regions are only supported by `coop-sweeper bot`")
+            }
+            (bot::Request::New(new_game), _) => match new_game.config() {
+                Ok(config) => {
+                    self.leave_game();
+                    self.seed = new_game.seed;
+                    self.no_guess = new_game.no_guess;
+                    self.rating_filter = new_game.rating;
+                    self.safe_zone = new_game.safe_zone();
+                    self.started = None;
+                    self.share_code = None;
+                    self.board_rating = None;
+                    self.state = Initialized(config);
+                    Response::board(&self.state, None)
+                }
+                Err(error) => Response::error(error),
+            },
+            (_, Ok(message)) => {
+                let moved = coop::move_request(&message).is_some();
+                let task = self.update(message, now);
+                self.face = Face::Happy;
+                self.assisted |= moved;
+                if self.generating.is_some() {
+                    self.bot_reply = Some(reply);
+                    return task;
+                }
+                Response::board(&self.state, self.share_code.as_ref())
+            }
+        };
+        let _ = reply.send(response);
+        Task::none()
+    }
+
     pub fn update(&mut self, message: Message, now: Instant) -> Task<Message> {
+        let task = self.dispatch(message, now);
+        if self.generating.is_none()
+            && let Some(reply) = self.bot_reply.take()
+        {
+            let response = match &self.generate_error {
+                Some(error) => Response::error(error.clone()),
+                None => Response::board(&self.state, self.share_code.as_ref()),
+            };
+            let _ = reply.send(response);
+        }
+        Task::batch([task, self.generate_queued()])
+    }
+
+    fn dispatch(&mut self, message: Message, now: Instant) -> Task<Message> {
         self.now = now;
+        match message {
+            Bot(command) => return self.handle_bot(command, now),
+            AutoStep => return self.auto_step(now),
+            Coop(event) => {
+                self.handle_coop(event);
+                return Task::none();
+            }
+            ZoomIn | ZoomOut | ZoomFit => {
+                self.settings.zoom = match message {
+                    ZoomIn => (self.settings.zoom + 1).min(MAX_ZOOM),
+                    ZoomOut => (self.settings.zoom - 1).max(-MAX_ZOOM),
+                    _ => 0,
+                };
+                self.settings.save();
+                return Task::none();
+            }
+            WindowFitBoard => return self.fit_window(),
+            AnimationSkip => {
+                self.animation = None;
+                return Task::none();
+            }
+            EditorCopyCode => {
+                return match self.editor.as_mut().and_then(Editor::copy_code) {
+                    Some(code) => clipboard::write(code),
+                    None => Task::none(),
+                };
+            }
+            _ => {}
+        }
+        if self.update_endless(&message) || self.update_editor(&message) {
+            return Task::none();
+        }
+        // Moves are animated by comparing the cells revealed before and after them.
+        let before = match (&message, &self.state) {
+            (RevealClick(loc) | ChordClick(loc) | FlagClick(loc), Started(grid)) => {
+                Some((*loc, Some(animation::revealed(grid))))
+            }
+            (BoardGenerated(code, Ok(..)), Initialized(..)) => Some((code.start, None)),
+            _ => None,
+        };
+        match self.coop {
+            Some(..) => self.play_coop(message),
+            None => self.play(message),
+        }
+        if let Some((origin, revealed)) = before {
+            self.animate(origin, revealed.as_deref());
+        }
+        Task::none()
+    }
+
+    /// Plays a message in a co-op game.
+    ///
+    /// Joined players send their moves to the host instead of playing them. The
+    /// host plays every move and tells everyone about it, along with new boards.
+    /// In a race, everyone plays their own moves and only reports their progress.
+    fn play_coop(&mut self, message: Message) {
+        let Some(coop) = &self.coop else {
+            return;
+        };
+        let race = coop.race;
+        let request = coop::move_request(&message);
+        if !coop.is_host() {
+            match (message, request) {
+                // The host picks the boards.
+                (
+                    GameNew | GameStart | DailyStart | EndlessStart | HotSeatStart | EditorStart
+                    | EditorPlay,
+                    _,
+                ) => {}
+                // Moves the host sent while the board was being generated wait for it.
+                (message @ BoardGenerated(..), _) => {
+                    self.play(message);
+                    if self.generating.is_none()
+                        && let Some(coop) = self.coop.as_mut()
+                    {
+                        for request in std::mem::take(&mut coop.moves) {
+                            if let Ok(message) = request.to_message(&self.state) {
+                                self.play(message);
+                            }
+                        }
+                    }
+                }
+                // Racers play separate copies of the board, so pointers are not shared.
+                (CoopCursor(..), _) if race => {}
+                // Racers wait for the host's board instead of starting one of their own.
+                (RevealClick(..), _) if race && matches!(self.state, Initialized(..)) => {}
+                (message, _) if race => self.play(message),
+                (_, Some(request)) => coop.send(CoopMessage::Move { request }),
+                (CoopCursor(loc), _) => coop.send(CoopMessage::Cursor {
+                    player: coop.player,
+                    loc,
+                }),
+                (message, None) => self.play(message),
+            }
+            self.report_progress();
+            return;
+        }
+        match message {
+            CoopCursor(..) if race => return,
+            CoopCursor(loc) => {
+                coop.send(CoopMessage::Cursor {
+                    player: coop::HOST,
+                    loc,
+                });
+                return;
+            }
+            // Endless and hot-seat boards are not shared.
+            EndlessStart | HotSeatStart => return,
+            _ => {}
+        }
+
+        let was_initialized = matches!(self.state, Initialized(..));
+        let was_started = matches!(self.state, Started(..));
+        let started = self.started;
+        self.play(message);
+        let Some(coop) = self.coop.as_mut() else {
+            return;
+        };
+        match (&self.state, &self.share_code, request) {
+            (Started(..) | Won(..) | Lost(..), Some(code), _) if self.started != started => {
+                coop.new_board();
+                coop.send(CoopMessage::Start {
+                    code: code.to_string(),
+                });
+            }
+            (_, _, Some(request)) if was_started && !race => {
+                coop.moves.push(request.clone());
+                coop.send(CoopMessage::Move { request });
+            }
+            (Initialized(config), _, _) if !was_initialized => {
+                coop.new_board();
+                coop.send(CoopMessage::Setup {
+                    config: config.clone(),
+                });
+            }
+            _ => {}
+        }
+        self.report_progress();
+    }
+
+    /// Tells the other racers how far this window got, whenever that changed.
+    fn report_progress(&mut self) {
+        let Some(coop) = self.coop.as_mut().filter(|coop| coop.race) else {
+            return;
+        };
+        let player = coop.player;
+        let previous = coop.progress.get(&player).copied();
+        let Some(progress) = Progress::of(&self.state, previous) else {
+            return;
+        };
+        if previous == Some(progress) {
+            return;
+        }
+        // Only the host decides who won, from the order progress reached it.
+        if coop.is_host() {
+            coop.record(player, progress);
+        } else {
+            coop.progress.insert(player, progress);
+        }
+        coop.send(CoopMessage::Progress { player, progress });
+    }
+
+    /// Reacts to news from the other players of a co-op game.
+    fn handle_coop(&mut self, event: CoopEvent) {
+        let Some(coop) = self.coop.as_mut() else {
+            return;
+        };
+        match event {
+            CoopEvent::Connected(link) => coop.connect(link),
+            // Brings the new player up to date with the board and everyone's cursors.
+            CoopEvent::Joined(player) => {
+                let race = coop.race;
+                coop.send_to(player, CoopMessage::Welcome { player, race });
+                match (&self.state, &self.share_code) {
+                    (Initialized(config), _) => coop.send_to(
+                        player,
+                        CoopMessage::Setup {
+                            config: config.clone(),
+                        },
+                    ),
+                    (Started(..) | Won(..) | Lost(..), Some(code)) => {
+                        let code = code.to_string();
+                        coop.send_to(player, CoopMessage::Start { code });
+                        for request in &coop.moves {
+                            let request = request.clone();
+                            coop.send_to(player, CoopMessage::Move { request });
+                        }
+                    }
+                    _ => {}
+                }
+                for (&other, &loc) in &coop.cursors {
+                    coop.send_to(player, CoopMessage::Cursor { player: other, loc });
+                }
+                // The winner goes first, so it is also the first to have won for the new player.
+                let mut racers: Vec<_> = coop.progress.iter().collect();
+                racers.sort_by_key(|&(&other, _)| Some(other) != coop.winner);
+                for (&other, &progress) in racers {
+                    let message = CoopMessage::Progress {
+                        player: other,
+                        progress,
+                    };
+                    coop.send_to(player, message);
+                }
+            }
+            CoopEvent::Left(player) if coop.is_host() => {
+                coop.cursors.remove(&player);
+                coop.progress.remove(&player);
+                coop.send(CoopMessage::Left { player });
+            }
+            CoopEvent::Left(..) => {
+                eprintln!("The co-op host left the game");
+                self.coop = None;
+            }
+            CoopEvent::Received { from, message } => {
+                self.receive_coop(from, message);
+                self.report_progress();
+            }
+        }
+    }
+
+    /// Plays what another player of a co-op game sent.
+    fn receive_coop(&mut self, from: u8, message: CoopMessage) {
+        let Some(coop) = self.coop.as_mut() else {
+            return;
+        };
+        match message {
+            CoopMessage::Move { request } if coop.is_host() => {
+                // Moves that do not fit the board, e.g. sent during a board change, are dropped.
+                if let Ok(message) = request.to_message(&self.state)
+                    && coop::move_request(&message).is_some()
+                {
+                    self.play_coop(message);
+                }
+            }
+            CoopMessage::Cursor { loc, .. } if coop.is_host() => {
+                coop.cursors.insert(from, loc);
+                coop.send(CoopMessage::Cursor { player: from, loc });
+            }
+            CoopMessage::Progress { progress, .. } if coop.is_host() => {
+                coop.record(from, progress);
+                coop.send(CoopMessage::Progress {
+                    player: from,
+                    progress,
+                });
+            }
+            _ if coop.is_host() => {}
+            CoopMessage::Welcome { player, race } => {
+                coop.player = player;
+                coop.race = race;
+            }
+            CoopMessage::Setup { config } => {
+                coop.new_board();
+                self.generating = None;
+                self.share_code = None;
+                self.board_rating = None;
+                self.started = None;
+                self.state = Initialized(config);
+            }
+            CoopMessage::Start { code } => match code.parse() {
+                Ok(code) => {
+                    coop.new_board();
+                    self.state = self.start_game(code);
+                }
+                Err(error) => eprintln!("Ignoring the co-op board: {error}"),
+            },
+            CoopMessage::Move { request } if self.generating.is_some() => coop.moves.push(request),
+            CoopMessage::Move { request } => {
+                if let Ok(message) = request.to_message(&self.state) {
+                    self.play(message);
+                }
+            }
+            CoopMessage::Cursor { player, loc } => {
+                if player != coop.player {
+                    coop.cursors.insert(player, loc);
+                }
+            }
+            CoopMessage::Progress { player, progress } => coop.record(player, progress),
+            CoopMessage::Left { player } => {
+                coop.cursors.remove(&player);
+                coop.progress.remove(&player);
+            }
+        }
+    }
+
+    /// Plays a message on the regular game, moving it to its next state.
+    fn play(&mut self, message: Message) {
         let state = std::mem::take(&mut self.state);
         self.state = match (message, state) {
             // (FaceHold, state) => {
@@ -98,54 +989,282 @@ impl App {
                 println!("FaceRelease");
                 state
             }
-            (GameNew, _) => CreationScreen(Difficulty::default().state.clone()),
+            (GameNew, _) => {
+                self.leave_game();
+                self.skins = skin::available();
+                CreationScreen(self.settings.difficulty().state.clone())
+            }
+            (AutoToggle, state) => {
+                self.auto_play = !self.auto_play;
+                state
+            }
+            (InputAutoDelay(delay), state) => {
+                self.auto_delay = delay;
+                state
+            }
+            (InputAutoGuess(auto_guess), state) => {
+                self.auto_guess = auto_guess;
+                state
+            }
             (ClickModeToggle, state) => {
                 self.click_mode.toggle();
                 state
             }
-            (InputRows(rows), CreationScreen(GridConfig { mines, mut size })) => {
-                size.rows = rows;
-                CreationScreen(GridConfig { mines, size })
+            (InputRows(rows), CreationScreen(mut config)) => {
+                config.size.rows = rows;
+                self.fit_mines(&mut config);
+                CreationScreen(config)
+            }
+            (InputCols(cols), CreationScreen(mut config)) => {
+                config.size.cols = cols;
+                self.fit_mines(&mut config);
+                CreationScreen(config)
+            }
+            (InputMines(mines), CreationScreen(config)) => {
+                self.density = None;
+                CreationScreen(GridConfig { mines, ..config })
+            }
+            (InputDensity(percent), CreationScreen(mut config)) => {
+                let density = percent as f32 / 100.0;
+                self.density = Some(density);
+                config.set_density(density, &self.safe_zone);
+                CreationScreen(config)
+            }
+            (InputTopology(topology), CreationScreen(config)) => {
+                let mut config = GridConfig { topology, ..config };
+                self.fit_mines(&mut config);
+                CreationScreen(config)
+            }
+            (InputWrap(wrap), CreationScreen(config)) => {
+                CreationScreen(GridConfig { wrap, ..config })
+            }
+            (InputNeighborhood(neighborhood), CreationScreen(config)) => {
+                let mut config = GridConfig {
+                    neighborhood,
+                    ..config
+                };
+                self.fit_mines(&mut config);
+                CreationScreen(config)
+            }
+            (InputMinesPerCell(mines_per_cell), CreationScreen(config)) => {
+                let mut config = GridConfig {
+                    mines_per_cell,
+                    ..config
+                };
+                self.fit_mines(&mut config);
+                CreationScreen(config)
+            }
+            // Presets only pick the size and mines, keeping the chosen variant.
+            (InputGridConfig(grid_config), CreationScreen(config)) => {
+                self.density = None;
+                CreationScreen(GridConfig {
+                    topology: config.topology,
+                    wrap: config.wrap,
+                    neighborhood: config.neighborhood,
+                    mines_per_cell: config.mines_per_cell,
+                    ..grid_config
+                })
+            }
+            (InputPresetName(name), state) => {
+                self.preset_name = name;
+                self.preset_error = None;
+                state
+            }
+            (PresetSave, CreationScreen(config)) => {
+                self.preset_error = self
+                    .presets
+                    .save_preset(&self.preset_name, config.clone())
+                    .err();
+                CreationScreen(config)
+            }
+            (PresetSelect(name), CreationScreen(config)) => match self.presets.get(&name) {
+                Some(preset) => {
+                    let config = preset.config.clone();
+                    self.density = None;
+                    self.preset_name = name;
+                    self.preset_error = None;
+                    CreationScreen(config)
+                }
+                None => CreationScreen(config),
+            },
+            (PresetDelete(name), state) => {
+                self.presets.delete(&name);
+                state
+            }
+            (InputNoGuess(no_guess), state) => {
+                self.no_guess = no_guess;
+                self.settings.no_guess = no_guess;
+                self.settings.save();
+                state
+            }
+            (InputRating(rating), state) => {
+                self.rating_filter = rating;
+                state
+            }
+            (InputColorScheme(color_scheme), state) => {
+                self.settings.color_scheme = color_scheme;
+                self.settings.save();
+                state
+            }
+            (InputReduceMotion(reduce_motion), state) => {
+                self.settings.reduce_motion = reduce_motion;
+                self.settings.save();
+                self.animation = None;
+                state
+            }
+            (InputSkin(name), state) => {
+                let (skin, skin_error) = skin::select(&name);
+                self.skin = Arc::new(skin);
+                self.skin_error = skin_error;
+                // A skin that failed to load was replaced by the built-in one.
+                self.settings.skin = self.skin.name.clone();
+                self.settings.save();
+                state
+            }
+            (InputSafeOpening(opening), state) => {
+                self.safe_zone = if opening {
+                    SafeZone::Opening
+                } else {
+                    SafeZone::Cell
+                };
+                self.settings.safe_opening = opening;
+                self.settings.save();
+                match state {
+                    CreationScreen(mut config) => {
+                        self.fit_mines(&mut config);
+                        CreationScreen(config)
+                    }
+                    state => state,
+                }
+            }
+            (InputChordStyle(chord_style), state) => {
+                self.settings.chord_style = chord_style;
+                self.settings.save();
+                state
+            }
+            (InputQuestionMarks(question_marks), state) => {
+                self.settings.question_marks = question_marks;
+                self.settings.save();
+                state
+            }
+            (InputDefaultDifficulty(name), state) => {
+                self.settings.difficulty = name.to_string();
+                self.settings.save();
+                state
+            }
+            (SettingsToggle, state) => {
+                self.show_settings = !self.show_settings;
+                state
+            }
+            (GameStart, CreationScreen(config)) => Initialized(config),
+            (HotSeatStart, CreationScreen(config)) => {
+                self.hot_seat = Some(HotSeat::default());
+                Initialized(config)
             }
-            (InputCols(cols), CreationScreen(GridConfig { mines, mut size })) => {
-                size.cols = cols;
-                CreationScreen(GridConfig { mines, size })
+            (DailyStart, _) => {
+                let date = Date::today();
+                self.daily = Some(date);
+                self.show_daily_history = false;
+                self.start_game(daily::challenge(date))
             }
-            (InputMines(mines), CreationScreen(GridConfig { size, .. })) => {
-                CreationScreen(GridConfig { mines, size })
+            (DailyHistoryToggle, state) => {
+                self.show_daily_history = !self.show_daily_history;
+                state
+            }
+            // The endless board keeps the chosen board's share of mines.
+            (EndlessStart, CreationScreen(config)) => {
+                let GridSize { rows, cols } = config.size;
+                let density = config.mines as f32 / (rows * cols) as f32;
+                let seed = self.seed.take().unwrap_or_else(rand::random);
+                self.endless = Some(EndlessBoard::new(seed, density));
+                self.started = None;
+                self.face = Face::Happy;
+                CreationScreen(config)
             }
-            (InputGridConfig(grid_config), CreationScreen(..)) => CreationScreen(grid_config),
-            (GameStart, CreationScreen(GridConfig { mines, size })) => {
-                Initialized(GridConfig { mines, size })
+            (EditorStart, CreationScreen(config)) => {
+                self.editor = Some(Editor::new(&config));
+                CreationScreen(config)
             }
-            (RevealClick(loc), Initialized(GridConfig { mines, size })) => {
+            (EditorPlay, state) => match self.editor.as_ref().map(Editor::share_code) {
+                Some(Ok(code)) => {
+                    self.editor = None;
+                    self.start_game(code)
+                }
+                Some(Err(error)) => {
+                    if let Some(editor) = &mut self.editor {
+                        editor.status = Some(error);
+                    }
+                    state
+                }
+                None => state,
+            },
+            (BoardGenerated(code, board), state) => self.board_generated(code, board, state),
+            // The first click waits for the board it started.
+            (RevealClick(..), state) if self.generating.is_some() => state,
+            (RevealClick(loc), Initialized(config)) => {
                 self.face = Face::Surprised;
-                let mut grid = Grid::new(size);
-                // use rand::SeedableRng;
-                // use rand_chacha::ChaCha20Rng;s
-                // let mut rng = ChaCha20Rng::seed_from_u64(6767);
-                // grid.populate_mines_with_rng(loc, mines, &mut rng);
-                grid.populate_mines(loc, mines);
-                print!("Initialized");
-                self.started = Some(self.now);
-                Started(grid)
+                let code = ShareCode {
+                    config,
+                    start: loc,
+                    seed: self.seed.take().unwrap_or_else(rand::random),
+                    opening: matches!(self.safe_zone, SafeZone::Opening),
+                    no_guess: self.no_guess,
+                    rating: self.rating_filter,
+                    layout: None,
+                };
+                // Only single-player games on a preset count towards its stats.
+                self.preset = match (&self.hot_seat, &self.coop) {
+                    (None, None) => self.presets.name_of(&code.config).map(str::to_string),
+                    _ => None,
+                };
+                // The first reveal is the first player's turn.
+                if let Some(hot_seat) = &mut self.hot_seat {
+                    hot_seat.pass();
+                }
+                self.start_game(code)
+            }
+            (message @ (RevealClick(..) | ChordClick(..) | FlagClick(..)), Started(mut grid))
+                if self.hot_seat.is_some() =>
+            {
+                self.face = Face::Surprised;
+                grid.clear_highlights(self.now);
+                if let Some(hot_seat) = &mut self.hot_seat {
+                    match message {
+                        RevealClick(loc) => hot_seat.reveal(&mut grid, loc),
+                        ChordClick(loc) => hot_seat.chord(&mut grid, loc),
+                        FlagClick(loc) => hot_seat.flag(&mut grid, loc),
+                        _ => {}
+                    }
+                }
+                GameState::resolve(grid, false)
             }
             (RevealClick(loc), Started(mut grid)) => {
                 self.face = Face::Surprised;
                 grid.clear_highlights(self.now);
                 let hit_mine = matches!(grid.cascade_reveal(loc), CellRevealResult::Mine);
-                Self::resolve_game_state(grid, hit_mine)
+                GameState::resolve(grid, hit_mine)
             }
             (ChordClick(loc), Started(mut grid)) => {
                 self.face = Face::Surprised;
                 grid.clear_highlights(self.now);
-                let hit_mine = matches!(grid.chord_reveal(loc), CellChordResult::Mines(..));
-                Self::resolve_game_state(grid, hit_mine)
+                let hit_mine = match grid.chord_reveal(loc) {
+                    CellChordResult::Mines(mines) => {
+                        grid.highlight_cells(mines, self.now);
+                        true
+                    }
+                    _ => false,
+                };
+                GameState::resolve(grid, hit_mine)
             }
             (FlagClick(loc), Started(mut grid)) => {
                 grid.clear_highlights(self.now);
-                grid.flag_cell(loc);
-                Self::resolve_game_state(grid, false)
+                grid.cycle_mark(loc, self.settings.question_marks);
+                GameState::resolve(grid, false)
+            }
+            (FlagAll(loc), Started(mut grid)) => {
+                grid.clear_highlights(self.now);
+                grid.set_flags(loc, grid.mines_per_cell());
+                GameState::resolve(grid, false)
             }
             (Quit, ..) => {
                 std::process::exit(0);
@@ -160,51 +1279,358 @@ impl App {
                 unreachable!("Unhandled message: {:?}, {:?}", message, state);
             }
         };
+        if matches!(self.state, Won(..) | Lost(..)) {
+            let won = matches!(self.state, Won(..));
+            let time = self
+                .started
+                .map(|started| self.now.saturating_duration_since(started))
+                .unwrap_or_default();
+            if let Some(date) = self.daily
+                && !self.assisted
+            {
+                self.daily_history.record(date, won, time);
+            }
+            if let Some(name) = self.preset.take()
+                && !self.assisted
+            {
+                self.presets.record(&name, won, time);
+                self.presets.save();
+            }
+        }
+    }
 
-        Task::none()
+    /// Keeps the mines within what a resized board holds around the safe zone,
+    /// at the density the player asked for if they did.
+    fn fit_mines(&self, config: &mut GridConfig) {
+        match self.density {
+            Some(density) => config.set_density(density, &self.safe_zone),
+            None => config.clamp_mines(&self.safe_zone),
+        }
     }
 
-    fn resolve_game_state(mut grid: Grid, hit_mine: bool) -> GameState {
-        if hit_mine {
-            grid.reveal_all();
-            Lost(grid)
-        } else if grid.is_won() {
-            grid.reveal_all();
-            Won(grid)
-        } else {
-            Started(grid)
+    /// Returns the canvas drawing `cells` with cells sized by `cell_side` to fit `space`.
+    fn board_canvas<'a>(
+        &'a self,
+        cells: BoardCells<'a>,
+        input: BoardInput,
+        space: Size,
+    ) -> BoardCanvas<'a> {
+        let (topology, size) = self.board_size();
+        let playing = matches!(input, BoardInput::FirstClick | BoardInput::Play(..));
+        let cursors = self
+            .coop
+            .iter()
+            .filter(|_| playing)
+            .flat_map(|coop| coop.cursors.iter().map(|(&player, &loc)| (loc, player)))
+            .collect();
+        BoardCanvas {
+            cells,
+            topology,
+            size,
+            side: Self::cell_side(self.settings.zoom, topology, size, space),
+            now: self.now,
+            input,
+            chord_style: self.settings.chord_style,
+            track_pointer: playing && self.coop.is_some(),
+            cursors,
+            // Endless boards play no effects.
+            animation: self
+                .animation
+                .as_ref()
+                .filter(|_| !matches!(cells, BoardCells::Endless(..))),
+            skin: &self.skin,
         }
     }
 
-    fn create_message_handler(&self, message: Message) -> Message {
-        match self.click_mode {
-            ClickMode::Reveal => message,
-            ClickMode::Flag => match message {
-                RevealClick(loc) => FlagClick(loc),
-                FlagClick(loc) => RevealClick(loc),
-                ChordClick(loc) => ChordClick(loc),
-                _ => unreachable!("Unhandled message: {:?}", message),
-            },
+    /// Draws the board on a canvas sized by `cell_side`, scrolling when zoomed
+    /// in past the window.
+    fn board_view<'a>(&'a self, cells: BoardCells<'a>, input: BoardInput) -> Element<'a, Message> {
+        responsive(move |space| {
+            let board = self.board_canvas(cells, input, space);
+            let bounds = board.bounds();
+            let board = canvas(board).width(bounds.width).height(bounds.height);
+            if bounds.width <= space.width && bounds.height <= space.height {
+                container(board).center(Length::Fill).into()
+            } else {
+                scrollable(board)
+                    .direction(Direction::Both {
+                        vertical: Scrollbar::default(),
+                        horizontal: Scrollbar::default(),
+                    })
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .into()
+            }
+        })
+        .into()
+    }
+
+    /// Renders the part of the endless board under the viewport, which pans by
+    /// dragging with the middle button.
+    fn endless_view<'a>(&'a self, board: &'a EndlessBoard) -> Element<'a, Message> {
+        let input = if board.is_over() {
+            BoardInput::Disabled
+        } else {
+            BoardInput::Play(self.click_mode)
+        };
+        let view = responsive(move |space| {
+            let board = self.board_canvas(BoardCells::Endless(board), input, space);
+            let side = board.side;
+            let bounds = board.bounds();
+            let board = mouse_area(canvas(board).width(bounds.width).height(bounds.height))
+                .on_middle_press(EndlessDragStart)
+                .on_middle_release(EndlessDragEnd)
+                .on_move(move |point| EndlessDrag(Point::new(point.x / side, point.y / side)));
+            container(board).center(Length::Fill).into()
+        });
+
+        let mut screen = stack![view];
+        if board.is_over() {
+            screen = screen.push(Self::overlay(
+                column![
+                    text("💥 Game Over! 💥").size(48),
+                    text(format!("You cleared {} cells", board.score())).size(24),
+                    row![
+                        button("Play Again")
+                            .on_press(EndlessStart)
+                            .padding(10)
+                            .style(button::success),
+                        button("Quit")
+                            .on_press(Quit)
+                            .padding(10)
+                            .style(button::danger),
+                    ]
+                    .spacing(10),
+                ]
+                .spacing(10)
+                .align_x(Alignment::Center),
+                0.7,
+            ));
         }
+        screen.into()
     }
 
-    /// Renders a grid with all interactions disabled (for game over states).
-    fn render_disabled_grid(&self, grid: &Grid) -> iced_grid::Grid<'_, Message> {
-        let buttons = (0..grid.rows()).flat_map(|row| {
-            (0..grid.cols()).map(move |col| {
-                grid.get(row, col).unwrap().display(
-                    grid.count_neighboring_mines(GridLoc { row, col }),
-                    NoOp,
-                    NoOp,
-                    NoOp,
-                    self.now,
-                )
-            })
+    /// Shows the board being drawn above the editor's tools.
+    fn editor_view<'a>(&'a self, editor: &'a Editor) -> Element<'a, Message> {
+        let verdict = editor.verdict.as_ref().map(|verdict| {
+            let verdict = match verdict {
+                Ok(Verdict {
+                    solvable: true,
+                    rating,
+                }) => format!("✔ Solvable without guessing · {rating}"),
+                Ok(Verdict { rating, .. }) => format!("✘ Needs guessing · {rating}"),
+                Err(error) => error.clone(),
+            };
+            text(verdict).size(14)
         });
-        iced_grid(buttons).columns(grid.cols())
+        let tools = column![
+            row![
+                button("Check").on_press(EditorCheck),
+                button("Play").on_press(EditorPlay).style(button::success),
+                button("Copy code")
+                    .on_press(EditorCopyCode)
+                    .style(button::secondary),
+                button("Done").on_press(GameNew).style(button::secondary),
+            ]
+            .spacing(10),
+            verdict,
+            row![
+                text_input("Board name", &editor.name)
+                    .on_input(InputEditorName)
+                    .on_submit(EditorExport)
+                    .width(150),
+                button("Export").on_press_maybe(
+                    Editor::validate_name(&editor.name)
+                        .is_ok()
+                        .then_some(EditorExport)
+                ),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            editor.status.as_deref().map(|status| text(status).size(14)),
+        ]
+        .spacing(10)
+        .align_x(Alignment::Center);
+        column![
+            self.board_view(BoardCells::Grid(&editor.grid), BoardInput::Edit),
+            tools
+        ]
+        .spacing(10)
+        .into()
     }
 
-    /// Creates a centered overlay container with semi-transparent background.
+    /// Shows the share code of the finished board so it can be replayed, and how hard it was.
+    fn board_info(&self) -> Element<'_, Message> {
+        let code = self
+            .share_code
+            .as_ref()
+            .map(|code| text(format!("Board code: {code}")).size(16));
+        let rating = self
+            .board_rating
+            .map(|rating| text(format!("Rating: {rating}")).size(16));
+        let daily = self
+            .daily
+            .map(|date| text(format!("Daily challenge {date}")).size(16));
+        column![daily, code, rating]
+            .spacing(5)
+            .align_x(Alignment::Center)
+            .into()
+    }
+
+    /// Lists past daily challenges, most recent first.
+    fn daily_history_view(&self) -> container::Container<'_, Message> {
+        let results = &self.daily_history.results;
+        let won = results.iter().filter(|result| result.won).count();
+        let rows = results.iter().rev().map(|result| {
+            let time = format!("{}:{:02}", result.seconds / 60, result.seconds % 60);
+            row![
+                text(&result.date).width(100),
+                text(if result.won { "Won" } else { "Lost" }).width(50),
+                text(time).width(60),
+                text(&result.code).size(12),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+            .into()
+        });
+        let summary = if results.is_empty() {
+            "No daily challenges played yet".to_string()
+        } else {
+            format!("Played {} · Won {won}", results.len())
+        };
+        Self::overlay(
+            column![
+                text("📅 Daily History").size(32),
+                text(summary),
+                scrollable(column(rows).spacing(5)).height(300),
+                button("Back")
+                    .on_press(DailyHistoryToggle)
+                    .padding(10)
+                    .style(button::secondary),
+            ]
+            .spacing(15)
+            .padding(30)
+            .align_x(Alignment::Center),
+            0.8,
+        )
+    }
+
+    /// Shows the preferences kept between runs.
+    fn settings_view(&self) -> container::Container<'_, Message> {
+        let setting = |label, control: Element<'static, Message>| {
+            row![text(label).width(150), control]
+                .spacing(10)
+                .align_y(Alignment::Center)
+        };
+        let small = |label| button(text(label).size(12)).style(button::secondary);
+        let difficulties: Vec<_> = Difficulty::DIFF_ALL.iter().map(|d| d.name).collect();
+        Self::overlay(
+            column![
+                text("⚙ Settings").size(32),
+                setting(
+                    "Theme",
+                    pick_list(
+                        ColorScheme::ALL,
+                        Some(self.settings.color_scheme),
+                        InputColorScheme
+                    )
+                    .into()
+                ),
+                setting(
+                    "Skin",
+                    pick_list(self.skins.clone(), Some(self.skin.name.clone()), InputSkin).into()
+                ),
+                self.skin_error.as_deref().map(|error| text(error).size(14)),
+                setting(
+                    "Animations",
+                    checkbox(self.settings.reduce_motion)
+                        .label("Reduce motion")
+                        .on_toggle(InputReduceMotion)
+                        .into()
+                ),
+                setting(
+                    "Chording",
+                    pick_list(
+                        ChordStyle::ALL,
+                        Some(self.settings.chord_style),
+                        InputChordStyle
+                    )
+                    .into()
+                ),
+                setting(
+                    "Flags",
+                    checkbox(self.settings.question_marks)
+                        .label("Question marks")
+                        .on_toggle(InputQuestionMarks)
+                        .into()
+                ),
+                setting(
+                    "First click",
+                    column![
+                        checkbox(self.settings.safe_opening)
+                            .label("Safe opening")
+                            .on_toggle(InputSafeOpening),
+                        checkbox(self.settings.no_guess)
+                            .label("No guessing")
+                            .on_toggle(InputNoGuess),
+                    ]
+                    .spacing(5)
+                    .into()
+                ),
+                setting(
+                    "Zoom",
+                    row![
+                        small("−").on_press(ZoomOut),
+                        text(format!("{:+}", self.settings.zoom)),
+                        small("+").on_press(ZoomIn),
+                        small("Fit").on_press(ZoomFit),
+                    ]
+                    .spacing(5)
+                    .align_y(Alignment::Center)
+                    .into()
+                ),
+                setting(
+                    "Default difficulty",
+                    pick_list(
+                        difficulties,
+                        Some(self.settings.difficulty().name),
+                        InputDefaultDifficulty
+                    )
+                    .into()
+                ),
+                button("Close")
+                    .on_press(SettingsToggle)
+                    .padding(10)
+                    .style(button::secondary),
+            ]
+            .spacing(15)
+            .padding(30)
+            .align_x(Alignment::Center),
+            0.9,
+        )
+    }
+
+    /// The button starting an endless board, explaining why when the chosen
+    /// rules cannot be played endlessly.
+    fn endless_button(config: &GridConfig) -> Element<'_, Message> {
+        let supported = EndlessBoard::supports(config);
+        let start = button("Endless")
+            .on_press_maybe((supported && config.validate().is_ok()).then_some(EndlessStart))
+            .padding(10)
+            .style(button::primary);
+        if supported {
+            start.into()
+        } else {
+            tooltip(
+                start,
+                "Endless boards are square, do not wrap, use the standard neighbors and one mine per cell",
+                tooltip::Position::Bottom,
+            )
+            .into()
+        }
+    }
+
+    /// Creates a centered overlay container with a semi-transparent background in the theme's colors.
     fn overlay<'a>(
         content: impl Into<Element<'a, Message>>,
         alpha: f32,
@@ -212,146 +1638,389 @@ impl App {
         container(content)
             .center_x(Length::Fill)
             .center_y(Length::Fill)
-            .style(move |_theme| container::Style {
-                background: Some(Background::Color(Color {
-                    a: alpha,
+            .style(move |theme: &Theme| {
+                let palette = theme.palette();
+                container::Style {
+                    background: Some(Background::Color(palette.background.scale_alpha(alpha))),
+                    text_color: Some(palette.text),
+                    border: Border::default(),
                     ..Default::default()
-                })),
-                text_color: Some(Color::WHITE),
-                border: Border::default(),
-                ..Default::default()
+                }
             })
     }
 
     pub fn view(&self) -> Element<'_, Message> {
-        let grid_inner: Element<'_, Message> = match &self.state {
-            CreationScreen(GridConfig { mines, size }) => {
-                let cells = (0..size.rows).flat_map(|_| {
-                    (0..size.cols).map(|_| (Cell::default()).display(0, NoOp, NoOp, NoOp, self.now))
-                });
-                let grid_view = iced_grid(cells).columns(size.cols);
-                let difficulties = row(Difficulty::DIFF_ALL.iter().map(Difficulty::display));
-
-                let overlay = Self::overlay(
-                    column![
-                        text("🎮 Minesweeper").size(32),
-                        difficulties,
-                        row![
-                            text("Rows:").width(60),
-                            number_input(&size.rows, 5..50, InputRows).width(100),
-                        ]
-                        .spacing(10)
-                        .align_y(Alignment::Center),
-                        row![
-                            text("Cols:").width(60),
-                            number_input(&size.cols, 5..50, InputCols).width(100),
-                        ]
+        let grid_inner: Element<'_, Message> = if let Some(board) = &self.endless {
+            self.endless_view(board)
+        } else if let Some(editor) = &self.editor {
+            self.editor_view(editor)
+        } else {
+            match &self.state {
+                CreationScreen(
+                    config @ GridConfig {
+                        mines,
+                        size,
+                        topology,
+                        wrap,
+                        neighborhood,
+                        mines_per_cell,
+                    },
+                ) => {
+                    let grid_view = self.board_view(BoardCells::Hidden, BoardInput::Disabled);
+                    let difficulties = row(Difficulty::DIFF_ALL.iter().map(Difficulty::display));
+                    let custom_presets = (!self.presets.custom.is_empty()).then(|| {
+                        row(self.presets.custom.iter().map(|preset| {
+                            row![
+                                button(text(&preset.name))
+                                    .on_press(PresetSelect(preset.name.clone()))
+                                    .style(button::secondary),
+                                button(text("✕").size(12))
+                                    .on_press(PresetDelete(preset.name.clone()))
+                                    .style(button::danger),
+                            ]
+                            .into()
+                        }))
                         .spacing(10)
-                        .align_y(Alignment::Center),
-                        row![
-                            text("Mines:").width(60),
-                            number_input(mines, 1..999, InputMines).width(100),
+                        .wrap()
+                    });
+                    let save_preset = row![
+                        text_input("Preset name", &self.preset_name)
+                            .on_input(InputPresetName)
+                            .on_submit(PresetSave)
+                            .width(150),
+                        button("Save preset").on_press_maybe(
+                            Presets::validate_name(&self.preset_name)
+                                .is_ok()
+                                .then_some(PresetSave)
+                        ),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center);
+                    let preset_stats = self.presets.name_of(config).and_then(|name| {
+                        let stats = self.presets.stats.get(name)?;
+                        Some(text(format!("{name}: {}", stats.summary())).size(14))
+                    });
+                    let daily = row![
+                        button("📅 Daily").on_press(DailyStart),
+                        button("History")
+                            .on_press(DailyHistoryToggle)
+                            .style(button::secondary),
+                    ]
+                    .spacing(10);
+
+                    let overlay = if self.show_daily_history {
+                        self.daily_history_view()
+                    } else {
+                        Self::overlay(
+                            column![
+                                text("🎮 Minesweeper").size(32),
+                                difficulties,
+                                custom_presets,
+                                save_preset,
+                                self.preset_error
+                                    .as_deref()
+                                    .map(|error| text(error).size(14)),
+                                preset_stats,
+                                daily,
+                                row![
+                                    text("Rows:").width(60),
+                                    number_input(&size.rows, GridConfig::SIZE_RANGE, InputRows)
+                                        .width(100),
+                                ]
+                                .spacing(10)
+                                .align_y(Alignment::Center),
+                                row![
+                                    text("Cols:").width(60),
+                                    number_input(&size.cols, GridConfig::SIZE_RANGE, InputCols)
+                                        .width(100),
+                                ]
+                                .spacing(10)
+                                .align_y(Alignment::Center),
+                                row![
+                                    text("Mines:").width(60),
+                                    number_input(
+                                        mines,
+                                        1..=config.max_mines(&self.safe_zone),
+                                        InputMines
+                                    )
+                                        .width(100),
+                                    text("Density %:"),
+                                    number_input(
+                                        &((config.density() * 100.0).round() as u32),
+                                        1..=(config.max_mines(&self.safe_zone) * 100)
+                                            .div_ceil(size.rows * size.cols)
+                                            as u32,
+                                        InputDensity
+                                    )
+                                    .width(80),
+                                    text("Per cell:"),
+                                    number_input(
+                                        mines_per_cell,
+                                        GridConfig::MINES_PER_CELL_RANGE,
+                                        InputMinesPerCell
+                                    )
+                                    .width(70),
+                                ]
+                                .spacing(10)
+                                .align_y(Alignment::Center),
+                                text(format!(
+                                    "{} · {}",
+                                    if *mines_per_cell > 1 {
+                                        format!("{:.2} mines for every cell", config.density())
+                                    } else {
+                                        format!("{:.1}% of cells hold a mine", config.density() * 100.0)
+                                    },
+                                    match config.no_guess_feasible(&self.safe_zone) {
+                                        Some(true) => "no-guess boards are likely feasible",
+                                        Some(false) => "likely too dense for no-guess boards",
+                                        None => "no-guess feasibility is only estimated for standard boards",
+                                    }
+                                ))
+                                .size(14),
+                                row![
+                                    checkbox(matches!(self.safe_zone, SafeZone::Opening))
+                                        .label("Safe opening")
+                                        .on_toggle(InputSafeOpening),
+                                    checkbox(self.no_guess)
+                                        .label("No guessing")
+                                        .on_toggle(InputNoGuess),
+                                    checkbox(*wrap).label("Wrap edges").on_toggle(InputWrap),
+                                ]
+                                .spacing(20),
+                                row![
+                                    text("Cells:"),
+                                    pick_list(Topology::ALL, Some(*topology), InputTopology),
+                                    text("Numbers count:"),
+                                    pick_list(
+                                        Neighborhood::ALL,
+                                        Some(*neighborhood),
+                                        InputNeighborhood
+                                    ),
+                                ]
+                                .spacing(10)
+                                .align_y(Alignment::Center),
+                                row![
+                                    text("Rating:"),
+                                    pick_list(Rating::ALL, self.rating_filter, |rating| {
+                                        InputRating(Some(rating))
+                                    })
+                                    .placeholder("Any"),
+                                    button("Any")
+                                        .on_press(InputRating(None))
+                                        .style(button::secondary),
+                                ]
+                                .spacing(10)
+                                .align_y(Alignment::Center),
+                                row![
+                                    text("Auto delay (ms):"),
+                                    number_input(
+                                        &self.auto_delay,
+                                        Self::AUTO_DELAY_RANGE,
+                                        InputAutoDelay
+                                    )
+                                    .step(50)
+                                    .width(100),
+                                    checkbox(self.auto_guess)
+                                        .label("Auto guesses")
+                                        .on_toggle(InputAutoGuess),
+                                ]
+                                .spacing(10)
+                                .align_y(Alignment::Center),
+                                config.validate().err().map(|error| text(error).size(14)),
+                                row![
+                                    button("Start Game")
+                                        .on_press_maybe(
+                                            config.validate().is_ok().then_some(GameStart)
+                                        )
+                                        .padding(10)
+                                        .style(button::success),
+                                    Self::endless_button(config),
+                                    button("2 Players")
+                                        .on_press_maybe(
+                                            config.validate().is_ok().then_some(HotSeatStart)
+                                        )
+                                        .padding(10)
+                                        .style(button::primary),
+                                    button("Editor")
+                                        .on_press_maybe(
+                                            config.validate().is_ok().then_some(EditorStart)
+                                        )
+                                        .padding(10)
+                                        .style(button::primary),
+                                    button("Quit")
+                                        .on_press(Quit)
+                                        .padding(10)
+                                        .style(button::danger),
+                                ]
+                                .spacing(10),
+                            ]
+                            .spacing(15)
+                            .padding(30)
+                            .align_x(Alignment::Center),
+                            0.8,
+                        )
+                    };
+
+                    stack![grid_view, overlay].into()
+                }
+                Initialized(..) if self.generating.is_some() => stack![
+                    self.board_view(BoardCells::Hidden, BoardInput::Disabled),
+                    Self::overlay(text("⏳ Generating the board…").size(24), 0.5),
+                ]
+                .into(),
+                Initialized(..) => match &self.generate_error {
+                    Some(error) => {
+                        let overlay = Self::overlay(
+                            column![
+                                text("😕 No matching board").size(32),
+                                text(error).size(14),
+                                button("New game").on_press(GameNew).padding(10),
+                            ]
+                            .spacing(10)
+                            .align_x(Alignment::Center),
+                            0.7,
+                        );
+                        stack![
+                            self.board_view(BoardCells::Hidden, BoardInput::Disabled),
+                            overlay
                         ]
-                        .spacing(10)
-                        .align_y(Alignment::Center),
-                        row![
-                            button("Start Game")
-                                .on_press(GameStart)
+                        .into()
+                    }
+                    None => self.board_view(BoardCells::Hidden, BoardInput::FirstClick),
+                },
+                Started(grid) => {
+                    self.board_view(BoardCells::Grid(grid), BoardInput::Play(self.click_mode))
+                }
+                Won(grid) => {
+                    let grid_view = self.board_view(BoardCells::Grid(grid), BoardInput::Disabled);
+
+                    let (title, subtitle) = match &self.hot_seat {
+                        Some(hot_seat) => {
+                            let [first, second] = hot_seat.scores;
+                            let title = match hot_seat.leader() {
+                                Some(player) => format!("🏆 Player {} wins! 🏆", player + 1),
+                                None => "🤝 It's a tie! 🤝".to_string(),
+                            };
+                            (title, format!("Player 1: {first} · Player 2: {second}"))
+                        }
+                        None => (
+                            "🎉 Game Won! 🎉".to_string(),
+                            "You found all the mines!".to_string(),
+                        ),
+                    };
+                    let overlay = Self::overlay(
+                        column![
+                            text(title).size(48),
+                            text(subtitle).size(24),
+                            self.board_info(),
+                            button("Quit")
+                                .on_press(Quit)
                                 .padding(10)
                                 .style(button::success),
+                        ]
+                        .spacing(10)
+                        .align_x(Alignment::Center),
+                        0.7,
+                    );
+
+                    // The result waits for the board's animation to play out.
+                    if self.is_animating() {
+                        grid_view
+                    } else {
+                        stack![grid_view, overlay].into()
+                    }
+                }
+                Lost(grid) => {
+                    let grid_view = self.board_view(BoardCells::Grid(grid), BoardInput::Disabled);
+
+                    let overlay = Self::overlay(
+                        column![
+                            text("💥 Game Over! 💥").size(48),
+                            text("You hit a mine!").size(24),
+                            self.board_info(),
                             button("Quit")
                                 .on_press(Quit)
                                 .padding(10)
                                 .style(button::danger),
                         ]
-                        .spacing(10),
-                    ]
-                    .spacing(15)
-                    .padding(30)
-                    .align_x(Alignment::Center),
-                    0.8,
-                );
-
-                stack![grid_view, overlay].into()
-            }
-            Initialized(GridConfig { size, .. }) => {
-                let buttons = (0..size.rows).flat_map(|row| {
-                    (0..size.cols).map(move |col| {
-                        (Cell::default()).display(
-                            0,
-                            RevealClick(GridLoc { row, col }),
-                            RevealClick(GridLoc { row, col }),
-                            RevealClick(GridLoc { row, col }),
-                            self.now,
-                        )
-                    })
-                });
-                iced_grid(buttons).columns(size.cols).into()
-            }
-            Started(grid) => {
-                let buttons = (0..grid.rows()).flat_map(|row| {
-                    (0..grid.cols()).map(move |col| {
-                        grid.get(row, col).unwrap().display(
-                            grid.count_neighboring_mines(GridLoc { row, col }),
-                            self.create_message_handler(RevealClick(GridLoc { row, col })),
-                            self.create_message_handler(ChordClick(GridLoc { row, col })),
-                            self.create_message_handler(FlagClick(GridLoc { row, col })),
-                            self.now,
-                        )
-                    })
-                });
-                iced_grid(buttons).columns(grid.cols()).into()
-            }
-            Won(grid) => {
-                let grid_view = self.render_disabled_grid(grid);
-
-                let overlay = Self::overlay(
-                    column![
-                        text("🎉 Game Won! 🎉").size(48),
-                        text("You found all the mines!").size(24),
-                        button("Quit")
-                            .on_press(Quit)
-                            .padding(10)
-                            .style(button::success),
-                    ]
-                    .spacing(10)
-                    .align_x(Alignment::Center),
-                    0.7,
-                );
-
-                stack![grid_view, overlay].into()
-            }
-            Lost(grid) => {
-                let grid_view = self.render_disabled_grid(grid);
-
-                let overlay = Self::overlay(
-                    column![
-                        text("💥 Game Over! 💥").size(48),
-                        text("You hit a mine!").size(24),
-                        button("Quit")
-                            .on_press(Quit)
-                            .padding(10)
-                            .style(button::danger),
-                    ]
-                    .spacing(10)
-                    .align_x(Alignment::Center),
-                    0.7,
-                );
+                        .spacing(10)
+                        .align_x(Alignment::Center),
+                        0.7,
+                    );
 
-                stack![grid_view, overlay].into()
+                    // The result waits for the board's animation to play out.
+                    if self.is_animating() {
+                        grid_view
+                    } else {
+                        stack![grid_view, overlay].into()
+                    }
+                }
             }
         };
+        let grid_inner = if self.show_settings {
+            stack![grid_inner, self.settings_view()].into()
+        } else {
+            grid_inner
+        };
+        let wraps = self.endless.is_none()
+            && match &self.state {
+                CreationScreen(config) | Initialized(config) => config.wrap,
+                Started(grid) | Won(grid) | Lost(grid) => grid.wraps(),
+            };
+        let color_scheme = self.settings.color_scheme;
+        let colors = self.skin.colors;
         let grid = container(grid_inner)
             .padding(20)
             .center_y(Length::Fill)
-            .style(|_theme| container::Style {
-                background: Some(GRID_CONTAINER_BACKGROUND_COLOR),
+            .style(move |_theme| container::Style {
+                background: Some(board_background(color_scheme, colors)),
+                // Wrapping boards are outlined, since their edges connect to each other.
+                border: if wraps {
+                    Border::default().color(WRAP_BORDER_COLOR).width(3)
+                } else {
+                    Border::default()
+                },
                 ..Default::default()
             });
-        container(column![header(self), grid])
-            .style(|_theme| container::Style {
-                background: Some(BACKGROUND_COLOR),
-                text_color: Some(TEXT_COLOR),
+        let caption = match (&self.endless, &self.coop) {
+            (Some(..), _) => {
+                Some("Drag with the middle button or use the arrow keys to explore".to_string())
+            }
+            (None, _) if self.editor.is_some() => {
+                Some("Right-click to add mines, click the cells the player starts with".to_string())
+            }
+            (None, Some(coop)) if coop.race => Some(match coop.winner {
+                Some(winner) if winner == coop.player => "You won the race!".to_string(),
+                Some(winner) => format!("Player {} won the race", winner + 1),
+                None => format!("Race: you are player {}", coop.player + 1),
+            }),
+            (None, Some(coop)) => Some(format!(
+                "Co-op: you are player {} · {} others connected",
+                coop.player + 1,
+                coop.cursors.len()
+            )),
+            (None, None) => wraps.then(|| "↔ Edges wrap around ↕".to_string()),
+        };
+        let caption =
+            caption.map(|caption| container(text(caption).size(14)).center_x(Length::Fill));
+        let small = |label| button(text(label).size(12)).style(button::secondary);
+        let zoom = container(
+            row![
+                text("Zoom").size(12),
+                small("−").on_press(ZoomOut),
+                small("Fit").on_press(ZoomFit),
+                small("+").on_press(ZoomIn),
+                small("Fit window").on_press(WindowFitBoard),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+        )
+        .padding(Padding::ZERO.top(5))
+        .center_x(Length::Fill);
+        container(column![header(self), caption, grid, zoom])
+            .style(move |_theme| container::Style {
+                background: Some(background(color_scheme, colors)),
+                text_color: Some(text_color(color_scheme, colors)),
                 ..Default::default()
             })
             .center(Length::Fill)
@@ -359,3 +2028,184 @@ impl App {
             .into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bot::Request, cell::CellType, layout::Layout, state::Difficulty};
+
+    fn board(app: &App) -> Vec<String> {
+        let (Started(grid) | Won(grid) | Lost(grid)) = &app.state else {
+            panic!("no board in {:?}", app.state);
+        };
+        let cols = grid.cols();
+        (0..grid.rows())
+            .flat_map(|row| (0..cols).map(move |col| (row, col)))
+            .map(|(row, col)| format!("{:?}", grid.get(row, col).unwrap().cell_type))
+            .collect()
+    }
+
+    /// Generates the board `app` waits for right away, instead of on another thread.
+    fn generate(app: &mut App) {
+        let code = app.queued_board.take().expect("no board to generate");
+        let _ = app.update(App::generate_board(code), Instant::now());
+    }
+
+    #[test]
+    fn test_cell_side() {
+        let size = GridSize { rows: 9, cols: 9 };
+        let space = Size::new(400.0, 300.0);
+        // 300 / 9 fits 33 pixels, snapped down to twice the sprites' size.
+        assert_eq!(App::cell_side(0, Topology::Square, size, space), 32.0);
+        assert_eq!(App::cell_side(2, Topology::Square, size, space), 64.0);
+        assert_eq!(App::cell_side(-1, Topology::Square, size, space), 16.0);
+        assert_eq!(App::cell_side(-5, Topology::Square, size, space), 16.0);
+        // Boards that do not fit at the sprites' size shrink below it.
+        let large = GridSize { rows: 50, cols: 50 };
+        assert_eq!(App::cell_side(0, Topology::Square, large, space), 6.0);
+        assert_eq!(App::cell_side(1, Topology::Square, large, space), 16.0);
+    }
+
+    #[test]
+    fn test_coop_replays_host_moves() {
+        let addr = "127.0.0.1:7979".parse().unwrap();
+        let mut host = App {
+            state: Initialized(Difficulty::DIFF_EASY.state.clone()),
+            coop: Some(Coop::new(CoopRole::Host(addr), false)),
+            ..App::default()
+        };
+        host.play_coop(RevealClick(GridLoc { row: 4, col: 4 }));
+        assert!(matches!(host.state, Initialized(..)));
+        generate(&mut host);
+        // The board is random, so flag a cell the opening left hidden.
+        let Started(grid) = &host.state else {
+            panic!("the first click should start the game");
+        };
+        let flag = grid
+            .locs()
+            .find(|loc| {
+                matches!(
+                    grid.get(loc.row, loc.col).unwrap().cell_type,
+                    CellType::Hidden
+                )
+            })
+            .unwrap();
+        host.play_coop(FlagClick(flag));
+        let moves = host.coop.as_ref().unwrap().moves.clone();
+        assert_eq!(moves.len(), 1);
+
+        let mut player = App {
+            coop: Some(Coop::new(CoopRole::Join(addr), false)),
+            ..App::default()
+        };
+        let code = host.share_code.as_ref().unwrap().to_string();
+        player.receive_coop(coop::HOST, CoopMessage::Start { code });
+        for request in moves {
+            player.receive_coop(coop::HOST, CoopMessage::Move { request });
+        }
+        // Moves sent while the board was being generated are played once it is ready.
+        generate(&mut player);
+        assert_eq!(board(&player), board(&host));
+
+        // Moves from players reach the host's board.
+        host.receive_coop(
+            1,
+            CoopMessage::Move {
+                request: Request::Flag(flag),
+            },
+        );
+        assert_eq!(host.coop.as_ref().unwrap().moves.len(), 2);
+        assert_ne!(board(&player), board(&host));
+    }
+
+    #[test]
+    fn test_bot_new_game_leaves_modes() {
+        let mut app = App {
+            endless: Some(EndlessBoard::new(1, 0.2)),
+            hot_seat: Some(HotSeat::default()),
+            auto_play: true,
+            ..App::default()
+        };
+        let (reply, response) = std::sync::mpsc::sync_channel(1);
+        let request = Request::New(bot::NewGame {
+            seed: Some(42),
+            ..bot::NewGame::default()
+        });
+        let _ = app.update(Bot(BotCommand { request, reply }), Instant::now());
+        assert!(matches!(response.recv().unwrap(), Response::Board(..)));
+        assert!(app.endless.is_none() && app.hot_seat.is_none() && !app.auto_play);
+        assert!(matches!(app.state, Initialized(..)));
+    }
+
+    #[test]
+    fn test_racer_waits_for_host_board() {
+        let addr = "127.0.0.1:7980".parse().unwrap();
+        let config = Difficulty::DIFF_EASY.state.clone();
+        let mut racer = App {
+            coop: Some(Coop::new(CoopRole::Join(addr), true)),
+            ..App::default()
+        };
+        racer.receive_coop(
+            coop::HOST,
+            CoopMessage::Setup {
+                config: config.clone(),
+            },
+        );
+        racer.play_coop(RevealClick(GridLoc { row: 0, col: 0 }));
+        assert!(matches!(racer.state, Initialized(..)));
+        assert!(racer.queued_board.is_none());
+
+        let mut host = App {
+            state: Initialized(config),
+            coop: Some(Coop::new(CoopRole::Host(addr), true)),
+            ..App::default()
+        };
+        host.play_coop(RevealClick(GridLoc { row: 4, col: 4 }));
+        generate(&mut host);
+        let code = host.share_code.as_ref().unwrap().to_string();
+        racer.receive_coop(coop::HOST, CoopMessage::Start { code });
+        generate(&mut racer);
+        assert_eq!(board(&racer), board(&host));
+    }
+
+    #[test]
+    fn test_auto_play_flags_full_cells() {
+        let rows = ["b....", ".....", ".o...", "...bb", "...b."].map(str::to_string);
+        let config = GridConfig {
+            size: GridSize { rows: 5, cols: 5 },
+            mines_per_cell: 2,
+            ..Difficulty::DIFF_EASY.state.clone()
+        };
+        let code = ShareCode::crafted(config, Layout::from_rows(&rows).unwrap()).unwrap();
+        let mut app = App {
+            state: GameState::resolve(code.generate().unwrap(), false),
+            ..App::default()
+        };
+        // The corner next to the opening's 2 must hold both mines a cell can.
+        let _ = app.auto_step(Instant::now());
+        let Started(grid) = &app.state else {
+            panic!("the game should go on");
+        };
+        assert_eq!(grid.get(0, 0).unwrap().cell_type, CellType::Flagged(2));
+    }
+
+    #[test]
+    fn test_auto_play_is_kept_out_of_stats() {
+        let mut app = App {
+            state: Initialized(Difficulty::DIFF_EASY.state.clone()),
+            seed: Some(42),
+            auto_guess: true,
+            ..App::default()
+        };
+        app.play(RevealClick(GridLoc { row: 4, col: 4 }));
+        generate(&mut app);
+        assert_eq!(app.preset.as_deref(), Some("Easy"));
+        let date = Date::today();
+        app.daily = Some(date);
+        while matches!(app.state, Started(..)) {
+            let _ = app.auto_step(Instant::now());
+        }
+        assert!(app.presets.stats.is_empty());
+        assert!(app.daily_history.get(date).is_none());
+    }
+}
@@ -1,12 +1,136 @@
-use rand::Rng;
-
-use crate::cell::{Cell, CellType};
-use std::{cmp::min, collections::HashSet, fmt, time::Instant};
+use rand::{Rng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cell::{Cell, CellType},
+    topology::{Neighborhood, Topology},
+};
+use std::{
+    cmp::min,
+    collections::{HashMap, HashSet},
+    fmt,
+    ops::{Range, RangeInclusive},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Instant,
+};
+
+/// Source of the revisions of every board, so no two boards share one.
+static REVISIONS: AtomicU64 = AtomicU64::new(1);
+
+/// Returns a revision no board has had before.
+pub fn next_revision() -> u64 {
+    REVISIONS.fetch_add(1, Ordering::Relaxed)
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct GridConfig {
     pub size: GridSize,
     pub mines: MinesAmt,
+    pub topology: Topology,
+    /// Whether the edges wrap around, so the board behaves like a torus.
+    pub wrap: bool,
+    /// Which cells around a cell its number counts.
+    pub neighborhood: Neighborhood,
+    /// Most mines a single cell can hold; more than 1 makes a multi-mine board.
+    pub mines_per_cell: u8,
+}
+
+impl GridConfig {
+    /// Allowed number of rows and columns for a board.
+    pub const SIZE_RANGE: Range<usize> = 5..201;
+    /// Allowed number of mines a single cell can hold.
+    pub const MINES_PER_CELL_RANGE: RangeInclusive<u8> = 1..=5;
+    /// Densest board no-guess generation reliably finds a board for, measured
+    /// with the benchmark on standard boards with a safe opening. It is only a
+    /// rough estimate, and other rules were not measured.
+    pub const NO_GUESS_MAX_DENSITY: f32 = 0.24;
+
+    fn cells(&self) -> usize {
+        self.size.rows * self.size.cols
+    }
+
+    /// Returns the most mines the board holds while keeping `safe_zone` clear
+    /// around the first click.
+    pub fn max_mines(&self, safe_zone: &SafeZone) -> usize {
+        self.cells().saturating_sub(safe_zone.reserved(self)) * self.mines_per_cell as usize
+    }
+
+    /// Returns the mines divided by the cells, which only exceeds 1 on multi-mine boards.
+    pub fn density(&self) -> f32 {
+        self.mines as f32 / self.cells().max(1) as f32
+    }
+
+    /// Sets the mines to the nearest count giving `density`, within what the board
+    /// holds around `safe_zone`.
+    pub fn set_density(&mut self, density: f32, safe_zone: &SafeZone) {
+        let mines = (density * self.cells() as f32).round() as usize;
+        self.mines = mines.clamp(1, self.max_mines(safe_zone).max(1));
+    }
+
+    /// Lowers the mines to what the board holds around `safe_zone`, after it
+    /// shrank or its rules changed.
+    pub fn clamp_mines(&mut self, safe_zone: &SafeZone) {
+        self.mines = self.mines.min(self.max_mines(safe_zone)).max(1);
+    }
+
+    /// Returns whether no-guess generation is likely to find a board this dense,
+    /// counting only the cells outside `safe_zone`.
+    ///
+    /// This is a rough estimate based on [`Self::NO_GUESS_MAX_DENSITY`], so it is
+    /// `None` for the rules it was not measured on: hexagonal, wrapping and
+    /// multi-mine boards and other neighborhoods.
+    pub fn no_guess_feasible(&self, safe_zone: &SafeZone) -> Option<bool> {
+        let standard = self.topology == Topology::Square
+            && !self.wrap
+            && self.neighborhood == Neighborhood::Standard
+            && self.mines_per_cell == 1;
+        let free = self.cells().saturating_sub(safe_zone.reserved(self)).max(1);
+        standard.then(|| self.mines as f32 / free as f32 <= Self::NO_GUESS_MAX_DENSITY)
+    }
+
+    /// Checks that a board of `size` fits the allowed size.
+    pub fn validate_size(size: GridSize) -> Result<(), String> {
+        if !Self::SIZE_RANGE.contains(&size.rows) || !Self::SIZE_RANGE.contains(&size.cols) {
+            return Err(format!(
+                "rows and columns must be between {} and {}",
+                Self::SIZE_RANGE.start,
+                Self::SIZE_RANGE.end - 1
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks that the board fits the allowed size and leaves at least one safe cell.
+    pub fn validate(&self) -> Result<(), String> {
+        Self::validate_size(self.size)?;
+        let rows = self.size.rows;
+        if !Self::MINES_PER_CELL_RANGE.contains(&self.mines_per_cell) {
+            return Err(format!(
+                "mines per cell must be between {} and {}",
+                Self::MINES_PER_CELL_RANGE.start(),
+                Self::MINES_PER_CELL_RANGE.end()
+            ));
+        }
+        // A safe opening falls back to a safe cell on boards too dense for it.
+        if self.mines == 0 || self.mines > self.max_mines(&SafeZone::Cell) {
+            return Err("mines must leave at least one safe cell".to_string());
+        }
+        if self.topology == Topology::Hex && self.neighborhood != Neighborhood::Standard {
+            return Err("hexagonal boards only support the standard neighborhood".to_string());
+        }
+        if self.wrap && self.topology == Topology::Hex && rows % 2 == 1 {
+            return Err("hexagonal boards need an even number of rows to wrap around".to_string());
+        }
+        Ok(())
+    }
+
+    /// Creates an empty grid with this configuration's size and rules, ready to be populated.
+    pub fn new_grid(&self) -> Grid {
+        Grid::new(self.size, self.topology)
+            .with_wrap(self.wrap)
+            .with_neighborhood(self.neighborhood)
+            .with_mines_per_cell(self.mines_per_cell)
+    }
 }
 
 pub enum CellRevealResult {
@@ -34,56 +158,121 @@ pub enum CellChordResult {
     OutOfBounds,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Grid {
     cells: Vec<Vec<Cell>>,
     populated: bool,
+    topology: Topology,
+    wrap: bool,
+    neighborhood: Neighborhood,
+    mines_per_cell: u8,
+    /// Players who claimed flagged cells in a hot-seat game.
+    flag_owners: HashMap<GridLoc, u8>,
+    /// Hidden cells the player marked with a question mark.
+    questions: HashSet<GridLoc>,
+    /// Mines the player revealed, losing the game.
+    exploded: HashSet<GridLoc>,
+    /// Changes whenever the cells may have, so drawings of the grid know when to redo.
+    revision: u64,
 }
 
 impl Grid {
     // ==================== Constructor ====================
 
-    /// Creates a new grid with the specified dimensions.
+    /// Creates a new grid with the specified dimensions, whose cells are
+    /// connected according to `topology`.
     ///
     /// All cells are initialized to their default state (hidden, no mine).
-    /// Call `populate_mines` to place mines after the first click.
-    pub fn new(size: GridSize) -> Self {
+    /// Call `populate_mines_with_rng` to place mines after the first click.
+    pub fn new(size: GridSize, topology: Topology) -> Self {
         Self {
             cells: vec![vec![Cell::default(); size.cols]; size.rows],
             populated: false,
+            topology,
+            wrap: false,
+            neighborhood: Neighborhood::Standard,
+            mines_per_cell: 1,
+            flag_owners: HashMap::new(),
+            questions: HashSet::new(),
+            exploded: HashSet::new(),
+            revision: next_revision(),
         }
     }
 
+    /// Makes the edges of the grid wrap around, so every cell has a full set of neighbors.
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Makes numbers count the cells of `neighborhood` instead of the touching ones.
+    pub fn with_neighborhood(mut self, neighborhood: Neighborhood) -> Self {
+        self.neighborhood = neighborhood;
+        self
+    }
+
+    /// Lets a single cell hold up to `mines_per_cell` mines.
+    pub fn with_mines_per_cell(mut self, mines_per_cell: u8) -> Self {
+        self.mines_per_cell = mines_per_cell;
+        self
+    }
+
     // ==================== Initialization ====================
 
-    /// Populates the grid with mines, avoiding the specified location.
+    /// Populates the grid with mines, keeping the `safe_zone` around the specified location clear.
     ///
     /// This ensures the first click is never a mine. After placing mines,
     /// automatically performs a cascade reveal from the clicked location.
-    pub fn populate_mines(&mut self, loc: GridLoc, mines: MinesAmt) {
-        self.populate_mines_with_rng(loc, mines, &mut rand::rng());
+    /// Boards are reproducible by passing a seeded RNG.
+    ///
+    /// Mines are placed with a partial Fisher–Yates shuffle over every cell outside
+    /// the safe zone, so generation time is independent of mine density. On
+    /// multi-mine grids every cell appears once per mine it can hold. If the safe
+    /// zone leaves less room than `mines`, every free cell is filled up.
+    ///
+    /// # Panics
+    /// Panics if the grid has already been populated.
+    pub fn populate_mines_with_rng<R: Rng>(
+        &mut self,
+        loc: GridLoc,
+        mines: MinesAmt,
+        safe_zone: &SafeZone,
+        rng: &mut R,
+    ) {
+        if self.populated {
+            unreachable!("Grid already populated");
+        }
+        self.touch();
+        let excluded = safe_zone.excluded_cells(self, loc, mines);
+        let slots = self.mines_per_cell as usize;
+        let mut candidates: Vec<GridLoc> = self
+            .locs()
+            .filter(|candidate| !excluded.contains(candidate))
+            .flat_map(|candidate| std::iter::repeat_n(candidate, slots))
+            .collect();
+        let amount = min(mines, candidates.len());
+        let (chosen, _) = candidates.partial_shuffle(rng, amount);
+        for mine in chosen {
+            self.cells[mine.row][mine.col].mines += 1;
+        }
+        self.populated = true;
+        self.cascade_reveal(loc);
     }
 
-    /// Populates the grid with mines using a custom RNG for deterministic testing.
+    /// Places mines where a hand-crafted board has them instead of at random,
+    /// leaving every cell hidden.
     ///
     /// # Panics
     /// Panics if the grid has already been populated.
-    pub fn populate_mines_with_rng<R: Rng>(&mut self, loc: GridLoc, mines: MinesAmt, rng: &mut R) {
+    pub fn populate_mines_at(&mut self, mines: impl IntoIterator<Item = (GridLoc, u8)>) {
         if self.populated {
             unreachable!("Grid already populated");
         }
-        for _ in 0..mines {
-            loop {
-                let x = rng.random_range(0..self.rows());
-                let y = rng.random_range(0..self.cols());
-
-                if (x, y) != (loc.row, loc.col) && !self.cells[x][y].is_mine {
-                    self.cells[x][y].is_mine = true;
-                    break;
-                }
-            }
+        self.touch();
+        for (loc, count) in mines {
+            self.cells[loc.row][loc.col].mines = count;
         }
-        self.cascade_reveal(loc);
+        self.populated = true;
     }
 
     // ==================== Dimension Accessors ====================
@@ -98,6 +287,27 @@ impl Grid {
         self.cells[0].len()
     }
 
+    /// Returns the shape of the cells.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Returns `true` if the edges wrap around to the opposite side.
+    pub fn wraps(&self) -> bool {
+        self.wrap
+    }
+
+    /// Returns the most mines a single cell can hold.
+    pub fn mines_per_cell(&self) -> u8 {
+        self.mines_per_cell
+    }
+
+    /// Returns an iterator over every location in the grid, in row-major order.
+    pub fn locs(&self) -> impl Iterator<Item = GridLoc> + use<> {
+        let cols = self.cols();
+        (0..self.rows()).flat_map(move |row| (0..cols).map(move |col| GridLoc { row, col }))
+    }
+
     // ==================== Cell Accessors ====================
 
     /// Returns a reference to the cell at the specified location, if valid.
@@ -107,28 +317,32 @@ impl Grid {
 
     /// Returns a mutable reference to the cell at the specified location, if valid.
     pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut Cell> {
+        self.touch();
         self.cells.get_mut(row)?.get_mut(col)
     }
 
+    /// Returns a number that changes whenever a cell may have changed.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn touch(&mut self) {
+        self.revision = next_revision();
+    }
+
     /// Returns an iterator over all neighboring cell locations (excluding the center).
     ///
-    /// Neighbors include all 8 adjacent cells (orthogonal and diagonal),
-    /// automatically clamped to grid boundaries.
-    pub fn neighbors(&self, loc: GridLoc) -> impl Iterator<Item = GridLoc> {
-        let start_r = loc.row.saturating_sub(1);
-        let end_r = min(self.rows() - 1, loc.row + 1);
-        let start_c = loc.col.saturating_sub(1);
-        let end_c = min(self.cols() - 1, loc.col + 1);
-
-        (start_r..=end_r).flat_map(move |r| {
-            (start_c..=end_c).filter_map(move |c| {
-                if r == loc.row && c == loc.col {
-                    None
-                } else {
-                    Some(GridLoc { row: r, col: c })
-                }
-            })
-        })
+    /// Which cells are adjacent depends on the grid's topology: all 8 surrounding
+    /// cells on a square grid, 6 on a hexagonal one, unless another neighborhood
+    /// was chosen. Neighbors are clamped to the grid boundaries, unless the grid
+    /// wraps around.
+    pub fn neighbors(&self, loc: GridLoc) -> impl Iterator<Item = GridLoc> + use<> {
+        let size = GridSize {
+            rows: self.rows(),
+            cols: self.cols(),
+        };
+        self.topology
+            .neighbors(self.neighborhood, loc, size, self.wrap)
     }
 
     // ==================== Counting & Queries ====================
@@ -138,42 +352,58 @@ impl Grid {
         self.cells
             .iter()
             .flatten()
-            .filter(|cell| cell.is_mine)
-            .count()
+            .map(|cell| cell.mines as usize)
+            .sum()
     }
 
-    /// Returns the total number of flagged cells in the grid.
+    /// Returns the total number of flags in the grid, counting every flag on multi-mine cells.
     pub fn count_flags(&self) -> usize {
         self.cells
             .iter()
             .flatten()
-            .filter(|cell| matches!(cell.cell_type, CellType::Flagged))
-            .count()
+            .map(|cell| Self::flags(cell) as usize)
+            .sum()
     }
 
-    /// Returns the count of flagged cells adjacent to the specified location.
+    /// Returns the number of flags adjacent to the specified location.
     pub fn count_neighboring_flags(&self, loc: GridLoc) -> u8 {
         self.neighbors(loc)
-            .filter(|n| matches!(self.cells[n.row][n.col].cell_type, CellType::Flagged))
-            .count() as u8
+            .map(|n| Self::flags(&self.cells[n.row][n.col]))
+            .sum()
     }
 
-    /// Returns the count of mines adjacent to the specified location.
+    /// Returns the number of mines adjacent to the specified location.
     ///
     /// This is the number displayed on revealed cells.
     pub fn count_neighboring_mines(&self, loc: GridLoc) -> u8 {
         self.neighbors(loc)
-            .filter(|n| self.cells[n.row][n.col].is_mine)
-            .count() as u8
+            .map(|n| self.cells[n.row][n.col].mines)
+            .sum()
+    }
+
+    fn flags(cell: &Cell) -> u8 {
+        match cell.cell_type {
+            CellType::Flagged(flags) => flags,
+            CellType::Hidden | CellType::Revealed => 0,
+        }
     }
 
-    /// Returns `true` if all mines are flagged.
+    /// Returns `true` if every mine is flagged, with as many flags as the cell holds mines.
     pub fn all_mines_flagged(&self) -> bool {
         self.cells
             .iter()
             .flatten()
-            .filter(|cell| cell.is_mine)
-            .all(|cell| matches!(cell.cell_type, CellType::Flagged))
+            .filter(|cell| cell.is_mine())
+            .all(|cell| Self::flags(cell) == cell.mines)
+    }
+
+    /// Returns `true` if every cell without a mine has been revealed.
+    pub fn all_safe_revealed(&self) -> bool {
+        self.cells
+            .iter()
+            .flatten()
+            .filter(|cell| !cell.is_mine())
+            .all(|cell| matches!(cell.cell_type, CellType::Revealed))
     }
 
     /// Returns `true` if the game is won.
@@ -181,6 +411,38 @@ impl Grid {
         self.count_mines() == self.count_flags() && self.all_mines_flagged()
     }
 
+    /// Returns the board's 3BV: the minimum number of left clicks needed to clear it.
+    ///
+    /// Every opening (connected region of empty cells, with its border) counts once,
+    /// plus every numbered cell that no opening reveals.
+    pub fn three_bv(&self) -> usize {
+        let is_empty = |loc: GridLoc| {
+            !self.cells[loc.row][loc.col].is_mine() && self.count_neighboring_mines(loc) == 0
+        };
+        let mut covered = HashSet::new();
+        let mut clicks = 0;
+        for loc in self.locs().filter(|&loc| is_empty(loc)) {
+            if covered.contains(&loc) {
+                continue;
+            }
+            clicks += 1;
+            let mut stack = vec![loc];
+            covered.insert(loc);
+            while let Some(current) = stack.pop() {
+                for neighbor in self.neighbors(current) {
+                    if covered.insert(neighbor) && is_empty(neighbor) {
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+        clicks
+            + self
+                .locs()
+                .filter(|loc| !self.cells[loc.row][loc.col].is_mine() && !covered.contains(loc))
+                .count()
+    }
+
     /// Returns `true` if any cell highlight animation is currently in progress.
     pub fn is_animating(&self, now: Instant) -> bool {
         self.cells
@@ -200,18 +462,18 @@ impl Grid {
         match cell.cell_type {
             CellType::Hidden => {
                 cell.cell_type = CellType::Revealed;
-                if cell.is_mine {
+                if cell.is_mine() {
                     CellRevealResult::Mine
                 } else {
                     CellRevealResult::Success
                 }
             }
             CellType::Revealed => CellRevealResult::AlreadyRevealed,
-            CellType::Flagged => CellRevealResult::Flagged,
+            CellType::Flagged(..) => CellRevealResult::Flagged,
         }
     }
 
-    /// Reveals a cell and recursively reveals all adjacent cells if there are no neighboring mines.
+    /// Reveals a cell and then every adjacent cell, spreading from cells with no neighboring mines.
     ///
     /// This implements the classic minesweeper "flood fill" behavior where clicking
     /// on an empty cell (0 neighboring mines) automatically reveals all connected
@@ -219,47 +481,29 @@ impl Grid {
     ///
     /// Returns `Mine` if a mine was revealed, otherwise `Success`.
     pub fn cascade_reveal(&mut self, loc: GridLoc) -> CellRevealResult {
-        self.cascade_reveal_recursive(&loc, &mut HashSet::new())
-    }
-
-    /// Internal recursive helper for cascade reveal.
-    ///
-    /// Uses a visited set to prevent infinite loops and redundant processing.
-    fn cascade_reveal_recursive(
-        &mut self,
-        loc: &GridLoc,
-        visited: &mut HashSet<GridLoc>,
-    ) -> CellRevealResult {
-        if loc.row >= self.rows() || loc.col >= self.cols() {
-            return CellRevealResult::OutOfBounds;
-        }
-
-        if visited.contains(loc) {
-            return CellRevealResult::Success;
-        }
-
-        let Some(cell) = self.get_mut(loc.row, loc.col) else {
-            return CellRevealResult::OutOfBounds;
-        };
-
-        match cell.cell_type {
-            CellType::Revealed => return CellRevealResult::AlreadyRevealed,
-            CellType::Flagged => return CellRevealResult::Flagged,
-            CellType::Hidden => {}
+        self.touch();
+        let result = self.reveal_cell(loc);
+        if matches!(result, CellRevealResult::Mine) {
+            self.exploded.insert(loc);
         }
-
-        cell.cell_type = CellType::Revealed;
-        if cell.is_mine {
-            return CellRevealResult::Mine;
+        if !matches!(result, CellRevealResult::Success) {
+            return result;
         }
 
-        visited.insert(*loc);
-
-        let neighboring_mines = self.count_neighboring_mines(*loc);
-        if neighboring_mines == 0 {
-            let neighbor_locs: Vec<_> = self.neighbors(*loc).collect();
+        // Openings on large boards can span thousands of cells, so the flood
+        // fill keeps its own stack rather than recursing.
+        let mut pending = vec![loc];
+        while let Some(loc) = pending.pop() {
+            if self.count_neighboring_mines(loc) != 0 {
+                continue;
+            }
+            let neighbor_locs: Vec<_> = self.neighbors(loc).collect();
             for neighbor in neighbor_locs {
-                self.cascade_reveal_recursive(&neighbor, visited);
+                let cell = &mut self.cells[neighbor.row][neighbor.col];
+                if matches!(cell.cell_type, CellType::Hidden) {
+                    cell.cell_type = CellType::Revealed;
+                    pending.push(neighbor);
+                }
             }
         }
 
@@ -284,7 +528,7 @@ impl Grid {
 
         match cell.cell_type {
             CellType::Hidden => return CellChordResult::Hidden,
-            CellType::Flagged => return CellChordResult::Flagged,
+            CellType::Flagged(..) => return CellChordResult::Flagged,
             CellType::Revealed => {}
         }
 
@@ -323,28 +567,94 @@ impl Grid {
 
     // ==================== Flag Operations ====================
 
-    /// Toggles the flag state of a cell.
+    /// Cycles the flag state of a cell.
     ///
     /// - Hidden cells become flagged
-    /// - Flagged cells become hidden (unflagged)
+    /// - Flagged cells get one more flag, up to the most mines a cell can hold
+    /// - Cells with the most flags become hidden (unflagged)
     /// - Revealed cells cannot be flagged
     pub fn flag_cell(&mut self, loc: GridLoc) -> CellFlagResult {
-        let Some(cell) = self.get_mut(loc.row, loc.col) else {
+        let most = self.mines_per_cell;
+        let Some(cell) = self.get(loc.row, loc.col) else {
             return CellFlagResult::OutOfBounds;
         };
-        match cell.cell_type {
-            CellType::Hidden => {
-                cell.cell_type = CellType::Flagged;
-                CellFlagResult::Success
-            }
-            CellType::Revealed => CellFlagResult::AlreadyRevealed,
-            CellType::Flagged => {
-                cell.cell_type = CellType::Hidden;
-                CellFlagResult::Success
+        let flags = match cell.cell_type {
+            CellType::Hidden => 1,
+            CellType::Flagged(flags) if flags < most => flags + 1,
+            CellType::Flagged(..) => 0,
+            CellType::Revealed => return CellFlagResult::AlreadyRevealed,
+        };
+        self.set_flags(loc, flags)
+    }
+
+    /// Like `flag_cell`, but with `question_marks` a cell holding every flag it
+    /// can gets a question mark next, before going back to hidden.
+    pub fn cycle_mark(&mut self, loc: GridLoc, question_marks: bool) -> CellFlagResult {
+        let Some(cell_type) = self.get(loc.row, loc.col).map(|cell| cell.cell_type) else {
+            return CellFlagResult::OutOfBounds;
+        };
+        self.touch();
+        match cell_type {
+            CellType::Flagged(flags) if question_marks && flags >= self.mines_per_cell => {
+                let result = self.set_flags(loc, 0);
+                self.questions.insert(loc);
+                result
             }
+            CellType::Hidden if self.questions.remove(&loc) => CellFlagResult::Success,
+            _ => self.flag_cell(loc),
         }
     }
 
+    /// Returns whether a hidden cell is marked with a question mark.
+    pub fn is_questioned(&self, loc: GridLoc) -> bool {
+        self.questions.contains(&loc)
+    }
+
+    /// Returns whether the player revealed the mine at `loc`.
+    pub fn exploded(&self, loc: GridLoc) -> bool {
+        self.exploded.contains(&loc)
+    }
+
+    /// Returns whether the player revealed a mine, losing the game.
+    pub fn is_lost(&self) -> bool {
+        !self.exploded.is_empty()
+    }
+
+    /// Marks a hidden or flagged cell with exactly `flags` flags, unflagging it at zero.
+    pub fn set_flags(&mut self, loc: GridLoc, flags: u8) -> CellFlagResult {
+        let Some(cell) = self.get(loc.row, loc.col) else {
+            return CellFlagResult::OutOfBounds;
+        };
+        if matches!(cell.cell_type, CellType::Revealed) {
+            return CellFlagResult::AlreadyRevealed;
+        }
+        self.touch();
+        self.questions.remove(&loc);
+        let cell = &mut self.cells[loc.row][loc.col];
+        cell.cell_type = match flags {
+            0 => CellType::Hidden,
+            flags => CellType::Flagged(flags),
+        };
+        CellFlagResult::Success
+    }
+
+    /// Flags every mine of a hidden cell on behalf of `player`, who then owns the flags.
+    pub fn claim_flags(&mut self, loc: GridLoc, player: u8) -> CellFlagResult {
+        let Some(cell) = self.get(loc.row, loc.col) else {
+            return CellFlagResult::OutOfBounds;
+        };
+        let result = self.set_flags(loc, cell.mines);
+        if matches!(result, CellFlagResult::Success) {
+            self.flag_owners.insert(loc, player);
+        }
+        result
+    }
+
+    /// Returns the player who claimed the flags on a cell, if any.
+    pub fn flag_owner(&self, loc: GridLoc) -> Option<u8> {
+        self.flag_owners.get(&loc).copied()
+    }
+
     // ==================== Highlight Operations ====================
 
     /// Activates the highlight animation on the specified cells.
@@ -396,13 +706,62 @@ impl fmt::Display for Grid {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+/// Policy deciding which cells are kept free of mines around the first click.
+#[derive(Clone, Debug, Default)]
+pub enum SafeZone {
+    /// Only the clicked cell is guaranteed to be safe.
+    #[default]
+    Cell,
+    /// The clicked cell and all of its neighbors are safe, guaranteeing an opening.
+    ///
+    /// Falls back to [`SafeZone::Cell`] when the board is too dense to keep the
+    /// whole neighborhood clear.
+    Opening,
+    /// An arbitrary region is kept free of mines, in addition to the clicked cell.
+    Region(HashSet<GridLoc>),
+}
+
+impl SafeZone {
+    /// Returns how many cells stay free of mines on a board with `config`, counting
+    /// a full neighborhood for an opening unless it would leave no room for mines.
+    fn reserved(&self, config: &GridConfig) -> usize {
+        match self {
+            SafeZone::Cell => 1,
+            SafeZone::Opening => {
+                let opening = 1 + config.topology.neighbor_count(config.neighborhood);
+                if opening < config.cells() { opening } else { 1 }
+            }
+            SafeZone::Region(region) => 1 + region.len(),
+        }
+    }
+
+    /// Returns the set of locations that must not receive a mine.
+    fn excluded_cells(&self, grid: &Grid, loc: GridLoc, mines: MinesAmt) -> HashSet<GridLoc> {
+        let mut excluded = HashSet::from([loc]);
+        match self {
+            SafeZone::Cell => {}
+            SafeZone::Opening => {
+                let opening: Vec<_> = grid.neighbors(loc).collect();
+                let total = grid.rows() * grid.cols();
+                if (total - 1 - opening.len()) * grid.mines_per_cell as usize >= mines {
+                    excluded.extend(opening);
+                }
+            }
+            SafeZone::Region(region) => excluded.extend(region),
+        }
+        excluded
+    }
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
 pub struct GridLoc {
     pub row: usize,
     pub col: usize,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct GridSize {
     pub rows: usize,
     pub cols: usize,
@@ -420,10 +779,242 @@ mod tests {
     #[test]
     fn test_grid_populate_mines() {
         let mut rng = ChaCha20Rng::seed_from_u64(6767);
-        let mut grid = Grid::new(GridSize { rows: 9, cols: 9 });
-        grid.populate_mines_with_rng(GridLoc { row: 4, col: 4 }, 10, &mut rng);
+        let mut grid = Grid::new(GridSize { rows: 9, cols: 9 }, Topology::Square);
+        grid.populate_mines_with_rng(GridLoc { row: 4, col: 4 }, 10, &SafeZone::Cell, &mut rng);
         // grid.populate_mines(GridLoc { row: 4, col: 4 }, 10);
+        assert_eq!(grid.count_mines(), 10);
         grid.reveal_all();
         println!("{}", grid);
     }
+
+    #[test]
+    fn test_grid_hex_cascade() {
+        // Odd rows are shifted right, so the corner does not touch the mine at (1, 1).
+        let mut grid = Grid::new(GridSize { rows: 3, cols: 3 }, Topology::Hex);
+        grid.cells[1][1].mines = 1;
+        assert_eq!(grid.count_neighboring_mines(GridLoc { row: 0, col: 0 }), 0);
+        assert_eq!(grid.count_neighboring_mines(GridLoc { row: 0, col: 2 }), 1);
+        grid.cascade_reveal(GridLoc { row: 0, col: 0 });
+        assert!(matches!(grid.cells[0][1].cell_type, CellType::Revealed));
+        assert!(matches!(grid.cells[1][0].cell_type, CellType::Revealed));
+        assert!(matches!(grid.cells[0][2].cell_type, CellType::Hidden));
+    }
+
+    #[test]
+    fn test_grid_wrapped_cascade() {
+        // The mine sits in the far corner, which touches the clicked corner when wrapping.
+        let mut grid = Grid::new(GridSize { rows: 5, cols: 5 }, Topology::Square).with_wrap(true);
+        grid.cells[4][4].mines = 1;
+        assert_eq!(grid.count_neighboring_mines(GridLoc { row: 0, col: 0 }), 1);
+        grid.cascade_reveal(GridLoc { row: 2, col: 2 });
+        assert!(matches!(grid.cells[0][0].cell_type, CellType::Revealed));
+        assert!(grid.all_safe_revealed());
+    }
+
+    #[test]
+    fn test_grid_knight_chord() {
+        // Numbers count knight moves, so chording reveals the cells a knight's move away.
+        let mut grid = Grid::new(GridSize { rows: 5, cols: 5 }, Topology::Square)
+            .with_neighborhood(Neighborhood::Knight);
+        grid.cells[1][2].mines = 1;
+        grid.cells[4][2].mines = 1;
+        grid.reveal_cell(GridLoc { row: 0, col: 0 });
+        assert_eq!(grid.count_neighboring_mines(GridLoc { row: 0, col: 0 }), 1);
+        grid.flag_cell(GridLoc { row: 1, col: 2 });
+        assert!(matches!(
+            grid.chord_reveal(GridLoc { row: 0, col: 0 }),
+            CellChordResult::Success
+        ));
+        assert!(matches!(grid.cells[2][1].cell_type, CellType::Revealed));
+        assert!(matches!(grid.cells[1][1].cell_type, CellType::Hidden));
+    }
+
+    #[test]
+    fn test_grid_exploded_mine() {
+        let mut grid = Grid::new(GridSize { rows: 5, cols: 5 }, Topology::Square);
+        grid.cells[1][2].mines = 1;
+        grid.cells[4][2].mines = 1;
+        assert!(matches!(
+            grid.cascade_reveal(GridLoc { row: 0, col: 0 }),
+            CellRevealResult::Success
+        ));
+        assert!(!grid.is_lost());
+        assert!(matches!(
+            grid.cascade_reveal(GridLoc { row: 1, col: 2 }),
+            CellRevealResult::Mine
+        ));
+        // Revealing the rest of the board afterwards does not set off the other mines.
+        grid.reveal_all();
+        assert!(grid.is_lost());
+        assert!(grid.exploded(GridLoc { row: 1, col: 2 }));
+        assert!(!grid.exploded(GridLoc { row: 4, col: 2 }));
+    }
+
+    #[test]
+    fn test_grid_revision() {
+        let mut grid = Grid::new(GridSize { rows: 5, cols: 5 }, Topology::Square);
+        let other = Grid::new(GridSize { rows: 5, cols: 5 }, Topology::Square);
+        assert_ne!(grid.revision(), other.revision());
+
+        let loc = GridLoc { row: 2, col: 2 };
+        let revision = grid.revision();
+        grid.count_neighboring_mines(loc);
+        assert_eq!(grid.revision(), revision);
+        grid.flag_cell(loc);
+        assert_ne!(grid.revision(), revision);
+        let revision = grid.revision();
+        grid.cycle_mark(loc, true);
+        assert_ne!(grid.revision(), revision);
+    }
+
+    #[test]
+    fn test_grid_multi_mine() {
+        let mut rng = ChaCha20Rng::seed_from_u64(6767);
+        let mut grid =
+            Grid::new(GridSize { rows: 5, cols: 5 }, Topology::Square).with_mines_per_cell(3);
+        let loc = GridLoc { row: 0, col: 0 };
+        grid.populate_mines_with_rng(loc, 60, &SafeZone::Cell, &mut rng);
+        assert_eq!(grid.count_mines(), 60);
+        assert!(
+            grid.locs()
+                .all(|loc| grid.cells[loc.row][loc.col].mines <= 3)
+        );
+        assert_eq!(grid.cells[0][0].mines, 0);
+
+        // A full board packs every free cell with as many mines as it can hold.
+        let mut full =
+            Grid::new(GridSize { rows: 5, cols: 5 }, Topology::Square).with_mines_per_cell(3);
+        full.populate_mines_with_rng(loc, 72, &SafeZone::Cell, &mut rng);
+        assert_eq!(full.count_neighboring_mines(loc), 9);
+
+        // Flags cycle through every count before clearing.
+        let corner = GridLoc { row: 4, col: 4 };
+        for flags in [1, 2, 3] {
+            grid.flag_cell(corner);
+            assert!(matches!(grid.cells[4][4].cell_type, CellType::Flagged(f) if f == flags));
+        }
+        grid.flag_cell(corner);
+        assert!(matches!(grid.cells[4][4].cell_type, CellType::Hidden));
+    }
+
+    #[test]
+    fn test_config_density() {
+        let mut config = GridConfig {
+            size: GridSize { rows: 10, cols: 10 },
+            mines: 20,
+            topology: Topology::Square,
+            wrap: false,
+            neighborhood: Neighborhood::Standard,
+            mines_per_cell: 1,
+        };
+        assert_eq!(config.max_mines(&SafeZone::Cell), 99);
+        assert_eq!(config.max_mines(&SafeZone::Opening), 91);
+        assert_eq!(config.no_guess_feasible(&SafeZone::Cell), Some(true));
+
+        config.set_density(0.3, &SafeZone::Cell);
+        assert_eq!(config.mines, 30);
+        assert_eq!(config.no_guess_feasible(&SafeZone::Cell), Some(false));
+        config.set_density(2.0, &SafeZone::Opening);
+        assert_eq!(config.mines, 91);
+        config.set_density(2.0, &SafeZone::Cell);
+        assert_eq!(config.mines, 99);
+
+        // Shrinking the board keeps the count within what it holds.
+        config.size = GridSize { rows: 5, cols: 5 };
+        config.clamp_mines(&SafeZone::Cell);
+        assert_eq!(config.mines, 24);
+        assert!(config.validate().is_ok());
+        config.clamp_mines(&SafeZone::Opening);
+        assert_eq!(config.mines, 16);
+
+        // The estimate was only measured on standard boards.
+        config.mines = 2;
+        assert_eq!(config.no_guess_feasible(&SafeZone::Opening), Some(true));
+        config.mines_per_cell = 2;
+        assert_eq!(config.no_guess_feasible(&SafeZone::Opening), None);
+    }
+
+    #[test]
+    fn test_grid_question_marks() {
+        let mut grid = Grid::new(GridSize { rows: 3, cols: 3 }, Topology::Square);
+        let loc = GridLoc { row: 1, col: 1 };
+        grid.cycle_mark(loc, true);
+        assert!(matches!(grid.cells[1][1].cell_type, CellType::Flagged(1)));
+        grid.cycle_mark(loc, true);
+        assert!(matches!(grid.cells[1][1].cell_type, CellType::Hidden));
+        assert!(grid.is_questioned(loc));
+        grid.cycle_mark(loc, true);
+        assert!(!grid.is_questioned(loc));
+
+        // Without question marks, flags go straight back to hidden.
+        grid.cycle_mark(loc, false);
+        grid.cycle_mark(loc, false);
+        assert!(!grid.is_questioned(loc));
+        assert!(matches!(grid.cells[1][1].cell_type, CellType::Hidden));
+    }
+
+    #[test]
+    fn test_grid_three_bv() {
+        // One opening covering the left side, plus the lone number in the far corner.
+        let mut grid = Grid::new(GridSize { rows: 3, cols: 5 }, Topology::Square);
+        grid.cells[0][3].mines = 1;
+        grid.cells[1][3].mines = 1;
+        grid.cells[2][3].mines = 1;
+        assert_eq!(grid.three_bv(), 4);
+    }
+
+    #[test]
+    fn test_grid_populate_mines_dense() {
+        let mut rng = ChaCha20Rng::seed_from_u64(6767);
+        let mut grid = Grid::new(GridSize { rows: 9, cols: 9 }, Topology::Square);
+        let loc = GridLoc { row: 0, col: 0 };
+        grid.populate_mines_with_rng(loc, 80, &SafeZone::Cell, &mut rng);
+        assert_eq!(grid.count_mines(), 80);
+        assert!(!grid.get(0, 0).unwrap().is_mine());
+    }
+
+    #[test]
+    fn test_grid_populate_mines_opening() {
+        let mut rng = ChaCha20Rng::seed_from_u64(6767);
+        let mut grid = Grid::new(GridSize { rows: 9, cols: 9 }, Topology::Square);
+        let loc = GridLoc { row: 4, col: 4 };
+        grid.populate_mines_with_rng(loc, 72, &SafeZone::Opening, &mut rng);
+        assert_eq!(grid.count_mines(), 72);
+        assert_eq!(grid.count_neighboring_mines(loc), 0);
+        assert!(!grid.get(4, 4).unwrap().is_mine());
+    }
+
+    #[test]
+    fn test_grid_populate_mines_opening_falls_back_when_dense() {
+        let mut rng = ChaCha20Rng::seed_from_u64(6767);
+        let mut grid = Grid::new(GridSize { rows: 9, cols: 9 }, Topology::Square);
+        let loc = GridLoc { row: 4, col: 4 };
+        grid.populate_mines_with_rng(loc, 75, &SafeZone::Opening, &mut rng);
+        assert_eq!(grid.count_mines(), 75);
+        assert!(!grid.get(4, 4).unwrap().is_mine());
+    }
+
+    #[test]
+    fn test_grid_populate_mines_region() {
+        let mut rng = ChaCha20Rng::seed_from_u64(6767);
+        let mut grid = Grid::new(GridSize { rows: 9, cols: 9 }, Topology::Square);
+        let region: HashSet<_> = (0..9).map(|col| GridLoc { row: 8, col }).collect();
+        let safe_zone = SafeZone::Region(region.clone());
+        let config = GridConfig {
+            size: GridSize { rows: 9, cols: 9 },
+            mines: 71,
+            topology: Topology::Square,
+            wrap: false,
+            neighborhood: Neighborhood::Standard,
+            mines_per_cell: 1,
+        };
+        assert_eq!(config.max_mines(&safe_zone), 71);
+        grid.populate_mines_with_rng(GridLoc { row: 0, col: 0 }, 71, &safe_zone, &mut rng);
+        assert_eq!(grid.count_mines(), 71);
+        assert!(
+            region
+                .iter()
+                .all(|loc| !grid.get(loc.row, loc.col).unwrap().is_mine())
+        );
+    }
 }
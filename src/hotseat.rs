@@ -0,0 +1,140 @@
+use crate::{
+    cell::CellType,
+    grid::{CellChordResult, Grid, GridLoc},
+};
+
+/// Number of players taking turns.
+pub const PLAYERS: usize = 2;
+
+/// A local game where two players take turns on one board and score a point
+/// for every mine they flag.
+///
+/// A correct flag claims the mine and the player goes again. Revealing a safe
+/// cell, or flagging one, which reveals it instead, passes the turn. Revealing
+/// a mine hands it to the other player. The game ends once every mine is claimed.
+#[derive(Clone, Debug, Default)]
+pub struct HotSeat {
+    /// The player whose turn it is.
+    pub turn: u8,
+    pub scores: [usize; PLAYERS],
+}
+
+impl HotSeat {
+    /// Hands the turn to the other player.
+    pub fn pass(&mut self) {
+        self.turn = (self.turn + 1) % PLAYERS as u8;
+    }
+
+    fn other(&self) -> u8 {
+        (self.turn + 1) % PLAYERS as u8
+    }
+
+    /// Gives the mines of a cell to `player`.
+    fn claim(&mut self, grid: &mut Grid, loc: GridLoc, player: u8) {
+        let mines = grid.get(loc.row, loc.col).map_or(0, |cell| cell.mines);
+        grid.claim_flags(loc, player);
+        self.scores[player as usize] += mines as usize;
+    }
+
+    /// Reveals a cell for the current player. Moves on cells that are not hidden are ignored.
+    pub fn reveal(&mut self, grid: &mut Grid, loc: GridLoc) {
+        let Some(cell) = grid.get(loc.row, loc.col) else {
+            return;
+        };
+        if !matches!(cell.cell_type, CellType::Hidden) {
+            return;
+        }
+        if cell.is_mine() {
+            self.claim(grid, loc, self.other());
+        } else {
+            grid.cascade_reveal(loc);
+        }
+        self.pass();
+    }
+
+    /// Flags a cell for the current player. Claimed flags cannot be taken back.
+    pub fn flag(&mut self, grid: &mut Grid, loc: GridLoc) {
+        let Some(cell) = grid.get(loc.row, loc.col) else {
+            return;
+        };
+        if !matches!(cell.cell_type, CellType::Hidden) {
+            return;
+        }
+        if cell.is_mine() {
+            self.claim(grid, loc, self.turn);
+        } else {
+            grid.cascade_reveal(loc);
+            self.pass();
+        }
+    }
+
+    /// Chords a revealed cell for the current player, which counts as a reveal.
+    ///
+    /// Every flag is on a mine, so chording never hits one.
+    pub fn chord(&mut self, grid: &mut Grid, loc: GridLoc) {
+        let hidden = |grid: &Grid| {
+            grid.neighbors(loc)
+                .filter(|n| matches!(grid.get(n.row, n.col).unwrap().cell_type, CellType::Hidden))
+                .count()
+        };
+        let before = hidden(grid);
+        if matches!(grid.chord_reveal(loc), CellChordResult::Success) && hidden(grid) < before {
+            self.pass();
+        }
+    }
+
+    /// Returns the player with the most points, or `None` on a tie.
+    pub fn leader(&self) -> Option<u8> {
+        let [first, second] = self.scores;
+        match first.cmp(&second) {
+            std::cmp::Ordering::Greater => Some(0),
+            std::cmp::Ordering::Less => Some(1),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{grid::GridSize, topology::Topology};
+
+    #[test]
+    fn test_hot_seat_turns() {
+        let mut grid = Grid::new(GridSize { rows: 5, cols: 5 }, Topology::Square);
+        for (row, col) in [(0, 1), (1, 0), (4, 4)] {
+            grid.get_mut(row, col).unwrap().mines = 1;
+        }
+        let mut game = HotSeat::default();
+        let find = |grid: &Grid, mine: bool| {
+            grid.locs().find(|loc| {
+                let cell = grid.get(loc.row, loc.col).unwrap();
+                cell.is_mine() == mine && matches!(cell.cell_type, CellType::Hidden)
+            })
+        };
+
+        // A correct flag scores and keeps the turn.
+        let mine = find(&grid, true).unwrap();
+        game.flag(&mut grid, mine);
+        assert_eq!((game.turn, game.scores), (0, [1, 0]));
+        assert_eq!(grid.flag_owner(mine), Some(0));
+        game.flag(&mut grid, mine);
+        assert_eq!(game.scores, [1, 0]);
+
+        // A wrong flag reveals the cell and passes the turn.
+        let safe = find(&grid, false).unwrap();
+        game.flag(&mut grid, safe);
+        assert!(matches!(
+            grid.get(safe.row, safe.col).unwrap().cell_type,
+            CellType::Revealed
+        ));
+        assert_eq!(game.turn, 1);
+
+        // Revealing a mine hands it to the other player.
+        let mine = find(&grid, true).unwrap();
+        game.reveal(&mut grid, mine);
+        assert_eq!((game.turn, game.scores), (0, [2, 0]));
+        assert_eq!(grid.flag_owner(mine), Some(0));
+        assert_eq!(game.leader(), Some(0));
+    }
+}
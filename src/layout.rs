@@ -0,0 +1,280 @@
+use std::{
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cell::CellType,
+    grid::{Grid, GridConfig, GridLoc, GridSize},
+    share::ShareCode,
+    storage,
+};
+
+/// What a cell of a hand-crafted board starts as.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Spot {
+    Hidden,
+    /// A safe cell the player sees from the start.
+    Revealed,
+    /// A cell holding this many mines.
+    Mines(u8),
+}
+
+impl Spot {
+    /// Returns the character standing for this cell: `.` for a hidden cell, `o`
+    /// for a revealed one, `*` for a mine and `b` to `e` for 2 to 5 mines.
+    fn symbol(self) -> char {
+        match self {
+            Self::Hidden => '.',
+            Self::Revealed => 'o',
+            Self::Mines(1) => '*',
+            Self::Mines(mines) => (b'a' + mines - 1) as char,
+        }
+    }
+
+    fn from_symbol(symbol: char) -> Option<Self> {
+        match symbol {
+            '.' => Some(Self::Hidden),
+            'o' => Some(Self::Revealed),
+            '*' => Some(Self::Mines(1)),
+            'b'..='e' => Some(Self::Mines(symbol as u8 - b'a' + 1)),
+            _ => None,
+        }
+    }
+}
+
+/// Where a hand-crafted board's mines are and which of its cells start revealed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Layout {
+    spots: Vec<Vec<Spot>>,
+}
+
+/// A hand-crafted board as saved by the editor: its rules and one string per row.
+#[derive(Deserialize, Serialize)]
+struct BoardFile {
+    config: GridConfig,
+    rows: Vec<String>,
+}
+
+impl Layout {
+    /// Reads the layout off a board drawn in the editor, where both the mines
+    /// and the cells starting revealed are revealed.
+    pub fn of(grid: &Grid) -> Self {
+        let spots = (0..grid.rows())
+            .map(|row| {
+                (0..grid.cols())
+                    .map(|col| {
+                        let cell = grid.get(row, col).unwrap();
+                        match cell.cell_type {
+                            _ if cell.is_mine() => Spot::Mines(cell.mines),
+                            CellType::Revealed => Spot::Revealed,
+                            _ => Spot::Hidden,
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { spots }
+    }
+
+    pub fn size(&self) -> GridSize {
+        GridSize {
+            rows: self.spots.len(),
+            cols: self.spots.first().map_or(0, Vec::len),
+        }
+    }
+
+    fn spots(&self) -> impl Iterator<Item = (GridLoc, Spot)> + '_ {
+        self.spots.iter().enumerate().flat_map(|(row, spots)| {
+            spots
+                .iter()
+                .enumerate()
+                .map(move |(col, &spot)| (GridLoc { row, col }, spot))
+        })
+    }
+
+    /// Returns how many mines the board holds in total.
+    pub fn mines(&self) -> usize {
+        self.spots()
+            .map(|(_, spot)| match spot {
+                Spot::Mines(mines) => mines as usize,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Returns the most mines any single cell holds.
+    pub fn most_mines(&self) -> u8 {
+        self.spots()
+            .filter_map(|(_, spot)| match spot {
+                Spot::Mines(mines) => Some(mines),
+                _ => None,
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns the cells that start revealed.
+    pub fn revealed(&self) -> impl Iterator<Item = GridLoc> + '_ {
+        self.spots()
+            .filter(|&(_, spot)| spot == Spot::Revealed)
+            .map(|(loc, _)| loc)
+    }
+
+    /// Builds the board to play with `config`'s rules. The revealed cells are
+    /// opened as if clicked, so empty ones cascade.
+    pub fn grid(&self, config: &GridConfig) -> Grid {
+        let mut grid = config.new_grid();
+        grid.populate_mines_at(self.spots().filter_map(|(loc, spot)| match spot {
+            Spot::Mines(mines) => Some((loc, mines)),
+            _ => None,
+        }));
+        for loc in self.revealed() {
+            grid.cascade_reveal(loc);
+        }
+        grid
+    }
+
+    /// Returns one string per row, as board files store them.
+    pub fn rows(&self) -> Vec<String> {
+        self.spots
+            .iter()
+            .map(|spots| spots.iter().map(|spot| spot.symbol()).collect())
+            .collect()
+    }
+
+    pub fn from_rows(rows: &[String]) -> Result<Self, String> {
+        let spots = rows
+            .iter()
+            .map(|row| {
+                row.chars()
+                    .map(|symbol| {
+                        Spot::from_symbol(symbol).ok_or_else(|| format!("unknown cell `{symbol}`"))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let cols = spots.first().map_or(0, Vec::len);
+        if cols == 0 || spots.iter().any(|row| row.len() != cols) {
+            return Err("rows must all have the same, non-zero length".to_string());
+        }
+        Ok(Self { spots })
+    }
+
+    /// Writes the cells row after row for share codes, with a run of identical
+    /// cells written once and followed by its length, e.g. `.12*o3`.
+    pub fn encode(&self) -> String {
+        let spots: Vec<Spot> = self.spots.iter().flatten().copied().collect();
+        let mut code = String::new();
+        for run in spots.chunk_by(|a, b| a == b) {
+            code.push(run[0].symbol());
+            if run.len() > 1 {
+                let _ = write!(code, "{}", run.len());
+            }
+        }
+        code
+    }
+
+    /// Reads the cells of a board of `size` back from `encode`.
+    pub fn decode(code: &str, size: GridSize) -> Result<Self, String> {
+        let cells = size.rows * size.cols;
+        let mut spots = Vec::with_capacity(cells);
+        let mut symbols = code.chars().peekable();
+        while let Some(symbol) = symbols.next() {
+            let spot =
+                Spot::from_symbol(symbol).ok_or_else(|| format!("unknown cell `{symbol}`"))?;
+            let mut run = String::new();
+            while let Some(digit) = symbols.next_if(char::is_ascii_digit) {
+                run.push(digit);
+            }
+            let run = match run.as_str() {
+                "" => 1,
+                run => run.parse().map_err(|_| format!("invalid run `{run}`"))?,
+            };
+            if run > cells - spots.len() {
+                return Err("more cells than the board has".to_string());
+            }
+            spots.extend(std::iter::repeat_n(spot, run));
+        }
+        if cells == 0 || spots.len() != cells {
+            return Err("fewer cells than the board has".to_string());
+        }
+        Ok(Self {
+            spots: spots.chunks(size.cols).map(<[Spot]>::to_vec).collect(),
+        })
+    }
+}
+
+/// Returns the directory the editor exports boards to.
+pub fn boards_dir() -> Option<PathBuf> {
+    storage::data_dir().map(|dir| dir.join("boards"))
+}
+
+/// Writes a hand-crafted board to `boards_dir` as `{name}.json`, returning where it went.
+pub fn save(name: &str, code: &ShareCode) -> io::Result<PathBuf> {
+    let layout = code
+        .layout
+        .as_ref()
+        .ok_or_else(|| io::Error::other("only hand-crafted boards can be saved"))?;
+    let dir = boards_dir().ok_or_else(|| io::Error::other("no home directory to save into"))?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{name}.json"));
+    let file = BoardFile {
+        config: code.config.clone(),
+        rows: layout.rows(),
+    };
+    fs::write(&path, serde_json::to_string_pretty(&file)?)?;
+    Ok(path)
+}
+
+/// Reads a board file into the share code that plays it.
+pub fn load(path: &Path) -> Result<ShareCode, String> {
+    let invalid = |error: String| format!("invalid board file `{}`: {error}", path.display());
+    let json = fs::read_to_string(path).map_err(|error| invalid(error.to_string()))?;
+    let file: BoardFile =
+        serde_json::from_str(&json).map_err(|error| invalid(error.to_string()))?;
+    let layout = Layout::from_rows(&file.rows).map_err(invalid)?;
+    ShareCode::crafted(file.config, layout).map_err(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Difficulty;
+
+    #[test]
+    fn test_layout_round_trip() {
+        let rows: Vec<String> = ["o.*..", "..*..", "***..", ".....", "....b"]
+            .map(str::to_string)
+            .into();
+        let layout = Layout::from_rows(&rows).unwrap();
+        assert_eq!(layout.rows(), rows);
+        assert_eq!(layout.mines(), 7);
+        assert_eq!(layout.most_mines(), 2);
+        assert_eq!(layout.encode(), "o.*.4*.2*3.11b");
+        assert_eq!(
+            Layout::decode("o.*.4*.2*3.11b", layout.size()),
+            Ok(layout.clone())
+        );
+        assert!(Layout::decode("o.*.4*.2*3.12b", layout.size()).is_err());
+        assert!(Layout::decode("o.*.4*.2*3.10b", layout.size()).is_err());
+        assert!(Layout::decode("o.*.4x.2*3.11b", layout.size()).is_err());
+
+        // The revealed corner is empty, so it opens its neighbors up to the wall of mines.
+        let config = GridConfig {
+            size: layout.size(),
+            mines_per_cell: 2,
+            ..Difficulty::DIFF_EASY.state.clone()
+        };
+        let grid = layout.grid(&config);
+        assert_eq!(grid.count_mines(), 7);
+        let opened = grid
+            .locs()
+            .filter(|loc| grid.get(loc.row, loc.col).unwrap().cell_type == CellType::Revealed)
+            .count();
+        assert_eq!(opened, 4);
+    }
+}
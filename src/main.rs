@@ -1,18 +1,114 @@
 mod algorithms;
+mod animation;
 mod assets;
+mod bench;
+mod board;
+mod bot;
 mod cell;
+mod cli;
+mod coop;
+mod daily;
+mod editor;
 mod elements;
+mod endless;
 mod game;
 mod grid;
+mod hotseat;
+mod layout;
 mod message;
+mod presets;
+mod rating;
+mod settings;
+mod share;
+mod skin;
+mod solver;
 mod state;
+mod storage;
 mod theme;
+mod topology;
+mod tui;
 
-use crate::game::App;
+use clap::Parser;
+
+use crate::{
+    bench::BenchOptions,
+    cli::{Cli, Command, LaunchOptions, LaunchState},
+    game::App,
+    settings::Settings,
+    state::Difficulty,
+};
 
 pub fn main() -> iced::Result {
-    iced::application::timed(App::default, App::update, App::subscription, App::view)
-        // .theme(theme::theme)
-        .window_size((500, 700))
-        .run()
+    let mut cli = Cli::parse();
+    let command = cli.command.take();
+    // Benchmarks and bots keep the same defaults whatever the player chose.
+    let preset = match command {
+        None | Some(Command::Tui) => Settings::load().difficulty(),
+        Some(..) => &Difficulty::DIFF_EASY,
+    };
+    let options = cli.into_launch_options(preset);
+    match command {
+        Some(Command::Tui) => {
+            if let Err(error) = tui::run(options) {
+                eprintln!("coop-sweeper: {error}");
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Some(Command::Bench {
+            boards,
+            opening,
+            json,
+        }) => {
+            run_bench(options, boards, opening, json);
+            Ok(())
+        }
+        Some(Command::Bot { listen }) => {
+            if let Err(error) = bot::run_server(listen) {
+                eprintln!("coop-sweeper: {error}");
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        None => run_window(options),
+    }
+}
+
+fn run_bench(options: LaunchOptions, boards: u64, opening: bool, json: bool) {
+    let (config, first_seed, opening) = match options.state {
+        LaunchState::Config(config) => (config, options.seed.unwrap_or_default(), opening),
+        LaunchState::Code(code) => (code.config, code.seed, code.opening),
+    };
+    let options = BenchOptions {
+        config,
+        first_seed,
+        boards,
+        opening,
+        no_guess: options.no_guess,
+        rating: options.rating,
+    };
+    if let Err(error) = options.validate() {
+        eprintln!("coop-sweeper: {error}");
+        std::process::exit(1);
+    }
+    let report = bench::run(&options);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else {
+        print!("{}", report.table());
+    }
+}
+
+fn run_window(options: LaunchOptions) -> iced::Result {
+    let window_size = (500.0 * options.scale, 700.0 * options.scale);
+    iced::application::timed(
+        move || App::new(&options),
+        App::update,
+        App::subscription,
+        App::view,
+    )
+    .theme(App::theme)
+    .scale_factor(App::scale_factor)
+    .window_size(window_size)
+    .run()
 }
@@ -1,4 +1,15 @@
-use crate::grid::{GridConfig, GridLoc};
+use iced::Point;
+
+use crate::{
+    bot::BotCommand,
+    coop::CoopEvent,
+    grid::{Grid, GridConfig, GridLoc},
+    rating::{BoardRating, Rating},
+    settings::ChordStyle,
+    share::ShareCode,
+    theme::ColorScheme,
+    topology::{Neighborhood, Topology},
+};
 
 #[derive(Clone, Debug)]
 pub enum Message {
@@ -10,18 +21,90 @@ pub enum Message {
     InputRows(usize),
     InputCols(usize),
     InputMines(usize),
+    /// Sets the mines to this percentage of the cells.
+    InputDensity(u32),
     InputGridConfig(GridConfig),
+    InputTopology(Topology),
+    InputWrap(bool),
+    InputNeighborhood(Neighborhood),
+    InputMinesPerCell(u8),
+    InputSafeOpening(bool),
+    InputNoGuess(bool),
+    InputRating(Option<Rating>),
+    InputSkin(String),
+    InputColorScheme(ColorScheme),
+    InputReduceMotion(bool),
+    InputChordStyle(ChordStyle),
+    InputQuestionMarks(bool),
+    /// Picks the preset the creation screen starts from, by name.
+    InputDefaultDifficulty(&'static str),
+    InputPresetName(String),
+    InputEditorName(String),
+
+    /// Saves the board on the creation screen as a custom preset under the name typed in.
+    PresetSave,
+    /// Loads the custom preset with this name into the creation screen.
+    PresetSelect(String),
+    PresetDelete(String),
 
     GameNew,
     GameStart,
+    EndlessStart,
+    HotSeatStart,
+    DailyStart,
+    DailyHistoryToggle,
+    SettingsToggle,
+
+    /// Opens the board editor on an empty board with the creation screen's size and rules.
+    EditorStart,
+    /// Lets the solver check whether the board being edited needs guessing.
+    EditorCheck,
+    /// Plays the board being edited.
+    EditorPlay,
+    /// Saves the board being edited as a board file.
+    EditorExport,
+    /// Copies the share code of the board being edited.
+    EditorCopyCode,
+
+    /// The board generated in the background for this share code, or why none was.
+    BoardGenerated(ShareCode, Result<(Grid, BoardRating), String>),
+
+    /// Moves the endless viewport by this many rows and columns.
+    EndlessPan(i64, i64),
+    EndlessDragStart,
+    /// The pointer moved over the endless board, measured in cells.
+    EndlessDrag(Point),
+    EndlessDragEnd,
 
     ClickModeToggle,
 
     RevealClick(GridLoc),
     ChordClick(GridLoc),
     FlagClick(GridLoc),
+    /// Flags a cell with as many flags as it can hold, as auto play does for the
+    /// mines the solver finds.
+    FlagAll(GridLoc),
 
     RequestHint,
+    /// Finishes the animation playing on the board at once.
+    AnimationSkip,
+
+    AutoToggle,
+    AutoStep,
+    InputAutoDelay(u64),
+    InputAutoGuess(bool),
+
+    Bot(BotCommand),
+    Coop(CoopEvent),
+    /// The pointer moved onto this cell during a co-op game.
+    CoopCursor(GridLoc),
+
+    ZoomIn,
+    ZoomOut,
+    /// Goes back to fitting the board in the window.
+    ZoomFit,
+    /// Resizes the window around the board.
+    WindowFitBoard,
 
     Quit,
 }
@@ -0,0 +1,163 @@
+use std::{collections::HashMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{grid::GridConfig, state::Difficulty, storage};
+
+/// File in the data directory holding the player's presets and their results.
+const PRESETS_FILE: &str = "presets.json";
+
+/// A board configuration the player saved under a name.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Preset {
+    pub name: String,
+    pub config: GridConfig,
+}
+
+/// Results of the games played on a preset.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PresetStats {
+    pub played: u32,
+    pub won: u32,
+    /// Fastest win, in seconds.
+    pub best_seconds: Option<u64>,
+}
+
+impl PresetStats {
+    /// Returns a one-line summary, e.g. `Played 4 · Won 3 · Best 1:05`.
+    pub fn summary(&self) -> String {
+        let mut summary = format!("Played {} · Won {}", self.played, self.won);
+        if let Some(seconds) = self.best_seconds {
+            summary += &format!(" · Best {}:{:02}", seconds / 60, seconds % 60);
+        }
+        summary
+    }
+}
+
+/// The player's presets, shown after the built-in ones, and the results of
+/// every preset, built-in ones included, by name.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Presets {
+    pub custom: Vec<Preset>,
+    pub stats: HashMap<String, PresetStats>,
+}
+
+impl Presets {
+    pub fn load() -> Self {
+        storage::load(PRESETS_FILE)
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = storage::save(PRESETS_FILE, self) {
+            eprintln!("coop-sweeper: could not save presets: {error}");
+        }
+    }
+
+    /// Returns the name of the preset, built-in or custom, with exactly this configuration.
+    pub fn name_of(&self, config: &GridConfig) -> Option<&str> {
+        Difficulty::DIFF_ALL
+            .iter()
+            .find(|difficulty| difficulty.state == config)
+            .map(|difficulty| difficulty.name)
+            .or_else(|| {
+                self.custom
+                    .iter()
+                    .find(|preset| &preset.config == config)
+                    .map(|preset| preset.name.as_str())
+            })
+    }
+
+    /// Checks that a custom preset can be saved under `name`.
+    pub fn validate_name(name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            Err("Name the preset to save it".to_string())
+        } else if Difficulty::named(name).is_some() {
+            Err(format!("{name} is a built-in preset"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Saves `config` as a custom preset, replacing any preset with the same name.
+    pub fn save_preset(&mut self, name: &str, config: GridConfig) -> Result<(), String> {
+        Self::validate_name(name)?;
+        config.validate()?;
+        let name = name.trim().to_string();
+        match self.custom.iter_mut().find(|preset| preset.name == name) {
+            Some(preset) => preset.config = config,
+            None => self.custom.push(Preset { name, config }),
+        }
+        self.save();
+        Ok(())
+    }
+
+    /// Deletes a custom preset along with its results.
+    pub fn delete(&mut self, name: &str) {
+        self.custom.retain(|preset| preset.name != name);
+        self.stats.remove(name);
+        self.save();
+    }
+
+    /// Returns the custom preset called `name`.
+    pub fn get(&self, name: &str) -> Option<&Preset> {
+        self.custom.iter().find(|preset| preset.name == name)
+    }
+
+    /// Records a finished game on the preset called `name`, to be kept with `save`.
+    pub fn record(&mut self, name: &str, won: bool, time: Duration) {
+        let stats = self.stats.entry(name.to_string()).or_default();
+        stats.played += 1;
+        if won {
+            stats.won += 1;
+            let seconds = time.as_secs();
+            stats.best_seconds = Some(stats.best_seconds.map_or(seconds, |best| best.min(seconds)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_names_and_stats() {
+        let mut presets = Presets::default();
+        let mut config = Difficulty::DIFF_EASY.state.clone();
+        assert_eq!(presets.name_of(&config), Some("Easy"));
+        assert!(Presets::validate_name("easy").is_err());
+        assert!(Presets::validate_name("  ").is_err());
+
+        config.mines = 20;
+        presets.custom.push(Preset {
+            name: "Dense".to_string(),
+            config: config.clone(),
+        });
+        assert_eq!(presets.name_of(&config), Some("Dense"));
+
+        let stats = presets.stats.entry("Dense".to_string()).or_default();
+        *stats = PresetStats {
+            played: 2,
+            won: 1,
+            best_seconds: Some(65),
+        };
+        assert_eq!(stats.summary(), "Played 2 · Won 1 · Best 1:05");
+    }
+
+    #[test]
+    fn test_preset_record() {
+        let mut presets = Presets::default();
+        presets.record("Easy", false, Duration::from_secs(30));
+        presets.record("Easy", true, Duration::from_secs(95));
+        presets.record("Easy", true, Duration::from_secs(80));
+        presets.record("Easy", true, Duration::from_secs(120));
+        let expected = PresetStats {
+            played: 4,
+            won: 3,
+            best_seconds: Some(80),
+        };
+        assert_eq!(presets.stats.get("Easy"), Some(&expected));
+        assert!(!presets.stats.contains_key("Hard"));
+    }
+}
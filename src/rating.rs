@@ -0,0 +1,166 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    grid::Grid,
+    solver::{self, DeductionLevel},
+};
+
+/// How hard a board is to clear, from the player's point of view.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rating {
+    Easy,
+    Medium,
+    Hard,
+    Evil,
+}
+
+impl Rating {
+    pub const ALL: [Self; 4] = [Self::Easy, Self::Medium, Self::Hard, Self::Evil];
+
+    /// Buckets a difficulty score as computed by `rate`.
+    fn from_score(score: f32) -> Self {
+        match score {
+            score if score < 3.0 => Self::Easy,
+            score if score < 5.0 => Self::Medium,
+            score if score < 8.0 => Self::Hard,
+            _ => Self::Evil,
+        }
+    }
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Easy => "Easy",
+            Self::Medium => "Medium",
+            Self::Hard => "Hard",
+            Self::Evil => "Evil",
+        })
+    }
+}
+
+impl FromStr for Rating {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|rating| rating.to_string().eq_ignore_ascii_case(name))
+            .ok_or_else(|| "expected one of Easy, Medium, Hard, Evil".to_string())
+    }
+}
+
+/// What the solver went through to clear a board, and the resulting rating.
+#[derive(Clone, Copy, Debug)]
+pub struct BoardRating {
+    pub rating: Rating,
+    /// Number of times nothing could be deduced and a guess was required.
+    pub guesses: usize,
+    /// Hardest reasoning needed anywhere on the board.
+    pub hardest: DeductionLevel,
+    /// 3BV divided by the number of safe cells: how much clicking the board needs.
+    pub three_bv_density: f32,
+}
+
+impl fmt::Display for BoardRating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let logic = match self.hardest {
+            DeductionLevel::Trivial => "simple logic",
+            DeductionLevel::Subset => "subset logic",
+        };
+        let guesses = match self.guesses {
+            1 => "1 guess".to_string(),
+            guesses => format!("{guesses} guesses"),
+        };
+        write!(
+            f,
+            "{} ({guesses}, {logic}, 3BV density {:.2})",
+            self.rating, self.three_bv_density
+        )
+    }
+}
+
+/// Rates a populated board by letting the solver clear it from its current state.
+///
+/// Whenever the solver is stuck it takes its best guess. The rating knows where
+/// the mines are, so a guess that would have been fatal is flagged instead and
+/// the run always reaches the end. Each point of 3BV density adds ten to the
+/// score, needing subset logic adds two and every guess adds three.
+pub fn rate(grid: &Grid) -> BoardRating {
+    let mut grid = grid.clone();
+    let mut guesses = 0;
+    let mut hardest = DeductionLevel::Trivial;
+    while !grid.all_safe_revealed() {
+        if let Some(step) = solver::deduce(&grid) {
+            hardest = hardest.max(step.level);
+            for deduction in step.deductions {
+                solver::apply(&mut grid, deduction);
+            }
+            continue;
+        }
+        let Some(loc) = solver::best_guess(&grid) else {
+            break;
+        };
+        guesses += 1;
+        let mines = grid.get(loc.row, loc.col).unwrap().mines;
+        if mines > 0 {
+            grid.set_flags(loc, mines);
+        } else {
+            grid.cascade_reveal(loc);
+        }
+    }
+
+    let safe_cells = grid.rows() * grid.cols() - grid.count_mines();
+    let three_bv_density = grid.three_bv() as f32 / safe_cells.max(1) as f32;
+    let logic_score = match hardest {
+        DeductionLevel::Trivial => 0.0,
+        DeductionLevel::Subset => 2.0,
+    };
+    let score = three_bv_density * 10.0 + logic_score + guesses as f32 * 3.0;
+    BoardRating {
+        rating: Rating::from_score(score),
+        guesses,
+        hardest,
+        three_bv_density,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        grid::{GridLoc, GridSize},
+        topology::Topology,
+    };
+
+    #[test]
+    fn test_rate_open_board_is_easy() {
+        let mut grid = Grid::new(GridSize { rows: 9, cols: 9 }, Topology::Square);
+        grid.get_mut(8, 8).unwrap().mines = 1;
+        grid.cascade_reveal(GridLoc { row: 0, col: 0 });
+        let rating = rate(&grid);
+        assert_eq!(rating.rating, Rating::Easy);
+        assert_eq!(rating.guesses, 0);
+        assert_eq!(rating.hardest, DeductionLevel::Trivial);
+    }
+
+    #[test]
+    fn test_rate_counts_forced_guesses() {
+        // Both cells of the first column touch the same two 1s: a 50/50.
+        let mut grid = Grid::new(GridSize { rows: 2, cols: 4 }, Topology::Square);
+        grid.get_mut(0, 0).unwrap().mines = 1;
+        grid.cascade_reveal(GridLoc { row: 0, col: 3 });
+        let rating = rate(&grid);
+        assert_eq!(rating.guesses, 1);
+        assert!(rating.rating >= Rating::Medium);
+    }
+
+    #[test]
+    fn test_rating_parses_case_insensitively() {
+        assert_eq!("hard".parse::<Rating>(), Ok(Rating::Hard));
+        assert!("impossible".parse::<Rating>().is_err());
+    }
+}
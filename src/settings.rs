@@ -0,0 +1,118 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{skin, state::Difficulty, storage, theme::ColorScheme};
+
+/// File in the config directory holding the player's preferences.
+const SETTINGS_FILE: &str = "settings.json";
+
+/// How revealed numbers are chorded.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChordStyle {
+    /// Clicking a revealed number chords it.
+    #[default]
+    Click,
+    /// Pressing both buttons on a revealed number chords it, as in the classic game.
+    BothButtons,
+    /// Revealed numbers ignore clicks.
+    Off,
+}
+
+impl ChordStyle {
+    pub const ALL: [ChordStyle; 3] = [ChordStyle::Click, ChordStyle::BothButtons, ChordStyle::Off];
+}
+
+impl fmt::Display for ChordStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChordStyle::Click => "Click",
+            ChordStyle::BothButtons => "Both buttons",
+            ChordStyle::Off => "Off",
+        })
+    }
+}
+
+/// Preferences kept between runs.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub color_scheme: ColorScheme,
+    pub skin: String,
+    /// Skips the animations played on the board after moves.
+    pub reduce_motion: bool,
+    pub chord_style: ChordStyle,
+    /// Whether flagging a cell once more marks it with a question mark.
+    pub question_marks: bool,
+    /// Whether the first click always opens an empty area.
+    pub safe_opening: bool,
+    pub no_guess: bool,
+    /// Sprite sizes added to, or removed from, the cell size fitting the board in the window.
+    pub zoom: i32,
+    /// Name of the preset the creation screen starts from.
+    pub difficulty: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            color_scheme: ColorScheme::default(),
+            skin: skin::BUILTIN.to_string(),
+            reduce_motion: false,
+            chord_style: ChordStyle::default(),
+            question_marks: false,
+            safe_opening: false,
+            no_guess: false,
+            zoom: 0,
+            difficulty: Difficulty::default().name.to_string(),
+        }
+    }
+}
+
+impl Settings {
+    /// Loads the preferences, using the defaults for any that were never saved.
+    pub fn load() -> Self {
+        storage::load_config(SETTINGS_FILE)
+    }
+
+    /// Saves the preferences, reporting failures without interrupting the game.
+    pub fn save(&self) {
+        if let Err(error) = storage::save_config(SETTINGS_FILE, self) {
+            eprintln!("coop-sweeper: could not save settings: {error}");
+        }
+    }
+
+    /// Returns the preset the creation screen starts from, or the default one
+    /// if the saved name is unknown.
+    pub fn difficulty(&self) -> &'static Difficulty {
+        Difficulty::named(&self.difficulty).unwrap_or(&Difficulty::DIFF_ALL[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_format() {
+        let settings = Settings {
+            color_scheme: ColorScheme::HighContrast,
+            chord_style: ChordStyle::BothButtons,
+            difficulty: "Hard".to_string(),
+            ..Settings::default()
+        };
+        let json = serde_json::to_string(&settings).unwrap();
+        assert!(json.contains(r#""color_scheme":"high_contrast""#));
+        assert!(json.contains(r#""chord_style":"both_buttons""#));
+        let settings: Settings = serde_json::from_str(&json).unwrap();
+        assert_eq!(settings.difficulty().name, "Hard");
+
+        // Settings missing from older files keep their defaults.
+        let settings: Settings = serde_json::from_str(r#"{"difficulty": "Impossible"}"#).unwrap();
+        assert_eq!(settings.color_scheme, ColorScheme::Dark);
+        assert_eq!(settings.skin, skin::BUILTIN);
+        assert!(!settings.reduce_motion);
+        assert_eq!(settings.difficulty().name, "Easy");
+    }
+}
@@ -0,0 +1,375 @@
+use std::{
+    fmt::{self, Write as _},
+    str::FromStr,
+};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use crate::{
+    grid::{Grid, GridConfig, GridLoc, GridSize, SafeZone},
+    layout::Layout,
+    rating::{self, Rating},
+    solver,
+    topology::{Neighborhood, Topology},
+};
+
+/// A compact code that reproduces a board exactly, so it can be shared with others.
+///
+/// Formatted as `{rows}x{cols}-{mines}-{row}.{col}-{seed}` where `row.col` is the
+/// first click and the seed is hexadecimal, followed by optional flags: `h` for
+/// hexagonal cells, `t` for edges that wrap around, `p`, `k` or `w` for numbers
+/// counting orthogonal, knight-move or 5x5 neighborhoods, `m` and a digit for the
+/// most mines a cell can hold, `o` for a guaranteed opening, `n` for no-guess
+/// generation and a digit for the required rating (`0` easy to `3` evil), e.g.
+/// `16x30-99-7.12-2a5f-on2`.
+///
+/// Hand-crafted boards are written `{rows}x{cols}-{flags}:{layout}` instead,
+/// with only the flags for the board's rules and the cells as `Layout::encode`
+/// writes them, e.g. `9x9:o.*.8*.69`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShareCode {
+    pub config: GridConfig,
+    pub start: GridLoc,
+    pub seed: u64,
+    pub opening: bool,
+    pub no_guess: bool,
+    /// Only boards with this rating are accepted during generation.
+    pub rating: Option<Rating>,
+    /// Where the mines of a hand-crafted board are, taking the place of the seed.
+    pub layout: Option<Layout>,
+}
+
+impl ShareCode {
+    /// Describes a hand-crafted board with `config`'s size and rules, holding
+    /// the layout's mines.
+    pub fn crafted(mut config: GridConfig, layout: Layout) -> Result<Self, String> {
+        if layout.size() != config.size {
+            return Err("the cells do not match the board's size".to_string());
+        }
+        if layout.most_mines() > config.mines_per_cell {
+            return Err("a cell holds more mines than the board allows".to_string());
+        }
+        config.mines = layout.mines();
+        config.validate()?;
+        let start = layout.revealed().next().unwrap_or_default();
+        Ok(Self {
+            config,
+            start,
+            seed: 0,
+            opening: false,
+            no_guess: false,
+            rating: None,
+            layout: Some(layout),
+        })
+    }
+
+    /// Generates the board described by this code, with the first click already revealed.
+    ///
+    /// Fails if no board passing the code's no-guess or rating filter turns up.
+    pub fn generate(&self) -> Result<Grid, String> {
+        let safe_zone = if self.opening {
+            SafeZone::Opening
+        } else {
+            SafeZone::Cell
+        };
+        self.generate_in(&safe_zone)
+    }
+
+    /// Like `generate`, but keeps `safe_zone` free of mines instead of what the
+    /// opening flag asks for. Boards kept clear around a `SafeZone::Region` cannot
+    /// be reproduced from the code afterwards.
+    pub fn generate_in(&self, safe_zone: &SafeZone) -> Result<Grid, String> {
+        if let Some(layout) = &self.layout {
+            return Ok(layout.grid(&self.config));
+        }
+        let mut rng = ChaCha20Rng::seed_from_u64(self.seed);
+        let accept = |grid: &Grid| match self.rating {
+            Some(target) => {
+                let rating = rating::rate(grid);
+                rating.rating == target && (!self.no_guess || rating.guesses == 0)
+            }
+            None => solver::is_solvable(grid),
+        };
+        if self.no_guess || self.rating.is_some() {
+            solver::generate_matching(&self.config, self.start, safe_zone, &mut rng, accept)
+                .ok_or_else(|| format!("no matching board was found for `{self}`"))
+        } else {
+            let mut grid = self.config.new_grid();
+            grid.populate_mines_with_rng(self.start, self.config.mines, safe_zone, &mut rng);
+            Ok(grid)
+        }
+    }
+}
+
+impl fmt::Display for ShareCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let GridSize { rows, cols } = self.config.size;
+        let mut flags = rule_flags(&self.config);
+        if let Some(layout) = &self.layout {
+            write!(f, "{rows}x{cols}")?;
+            if !flags.is_empty() {
+                write!(f, "-{flags}")?;
+            }
+            return write!(f, ":{}", layout.encode());
+        }
+        write!(
+            f,
+            "{rows}x{cols}-{}-{}.{}-{:x}",
+            self.config.mines, self.start.row, self.start.col, self.seed
+        )?;
+        if self.opening {
+            flags.push('o');
+        }
+        if self.no_guess {
+            flags.push('n');
+        }
+        if let Some(rating) = self.rating {
+            let _ = write!(flags, "{}", rating as u8);
+        }
+        if !flags.is_empty() {
+            write!(f, "-{flags}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the flags for a board's rules, which every kind of code writes first.
+fn rule_flags(config: &GridConfig) -> String {
+    let mut flags = String::new();
+    if config.topology == Topology::Hex {
+        flags.push('h');
+    }
+    if config.wrap {
+        flags.push('t');
+    }
+    match config.neighborhood {
+        Neighborhood::Standard => {}
+        Neighborhood::Orthogonal => flags.push('p'),
+        Neighborhood::Knight => flags.push('k'),
+        Neighborhood::Wide => flags.push('w'),
+    }
+    if config.mines_per_cell > 1 {
+        let _ = write!(flags, "m{}", config.mines_per_cell);
+    }
+    flags
+}
+
+/// Everything the flags at the end of a share code can ask for.
+#[derive(Default)]
+struct Flags {
+    hex: bool,
+    wrap: bool,
+    neighborhood: Option<Neighborhood>,
+    mines_per_cell: Option<u8>,
+    opening: bool,
+    no_guess: bool,
+    rating: Option<Rating>,
+}
+
+impl Flags {
+    /// Reads the flags, or returns `None` if one is unknown or asked for twice.
+    fn parse(flags: &str) -> Option<Self> {
+        let mut parsed = Self::default();
+        let mut chars = flags.chars();
+        while let Some(flag) = chars.next() {
+            match flag {
+                'h' => parsed.hex = true,
+                't' => parsed.wrap = true,
+                'o' => parsed.opening = true,
+                'n' => parsed.no_guess = true,
+                'p' | 'k' | 'w' if parsed.neighborhood.is_none() => {
+                    parsed.neighborhood = Some(match flag {
+                        'p' => Neighborhood::Orthogonal,
+                        'k' => Neighborhood::Knight,
+                        _ => Neighborhood::Wide,
+                    });
+                }
+                'm' if parsed.mines_per_cell.is_none() => {
+                    let digit = chars.next()?.to_digit(10)?;
+                    parsed.mines_per_cell = Some(digit as u8);
+                }
+                '0'..='3' if parsed.rating.is_none() => {
+                    parsed.rating = Some(Rating::ALL[flag as usize - '0' as usize]);
+                }
+                _ => return None,
+            }
+        }
+        Some(parsed)
+    }
+
+    /// Returns a board of `size` with `mines` under the rules the flags ask for.
+    fn config(&self, size: GridSize, mines: usize) -> GridConfig {
+        GridConfig {
+            size,
+            mines,
+            topology: if self.hex {
+                Topology::Hex
+            } else {
+                Topology::Square
+            },
+            wrap: self.wrap,
+            neighborhood: self.neighborhood.unwrap_or_default(),
+            mines_per_cell: self.mines_per_cell.unwrap_or(1),
+        }
+    }
+}
+
+impl FromStr for ShareCode {
+    type Err = String;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid share code `{code}`");
+        let number = |part: &str| part.parse::<usize>().map_err(|_| invalid());
+        let size = |part: &str| {
+            let (rows, cols) = part.split_once('x').ok_or_else(invalid)?;
+            Ok::<_, String>(GridSize {
+                rows: number(rows)?,
+                cols: number(cols)?,
+            })
+        };
+
+        if let Some((board, layout)) = code.trim().split_once(':') {
+            let (board_size, flags) = board.split_once('-').unwrap_or((board, ""));
+            // Hand-crafted boards are not generated, so only the rules apply.
+            let flags = Flags::parse(flags)
+                .filter(|flags| !flags.opening && !flags.no_guess && flags.rating.is_none())
+                .ok_or_else(invalid)?;
+            let size = size(board_size)?;
+            // The size is checked before the cells are read into a board that large.
+            GridConfig::validate_size(size)
+                .map_err(|error| format!("share code `{code}` is not a valid board: {error}"))?;
+            let layout = Layout::decode(layout, size)
+                .map_err(|error| format!("share code `{code}` has invalid cells: {error}"))?;
+            return Self::crafted(flags.config(size, 0), layout)
+                .map_err(|error| format!("share code `{code}` is not a valid board: {error}"));
+        }
+
+        let mut parts = code.trim().split('-');
+        let (board_size, mines, start, seed) =
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(size), Some(mines), Some(start), Some(seed)) => (size, mines, start, seed),
+                _ => return Err(invalid()),
+            };
+        let flags = Flags::parse(parts.next().unwrap_or_default()).ok_or_else(invalid)?;
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        let size = size(board_size)?;
+        let (row, col) = start.split_once('.').ok_or_else(invalid)?;
+        let start = GridLoc {
+            row: number(row)?,
+            col: number(col)?,
+        };
+        let mines = number(mines)?;
+        GridConfig::validate_size(size)
+            .map_err(|error| format!("share code `{code}` is not a valid board: {error}"))?;
+        if start.row >= size.rows || start.col >= size.cols {
+            return Err(invalid());
+        }
+        let config = flags.config(size, mines);
+        if mines >= size.rows * size.cols * config.mines_per_cell as usize {
+            return Err(format!(
+                "share code `{code}` has too many mines for its board"
+            ));
+        }
+        if let Err(error) = config.validate() {
+            return Err(format!("share code `{code}` is not a valid board: {error}"));
+        }
+
+        Ok(Self {
+            config,
+            start,
+            seed: u64::from_str_radix(seed, 16).map_err(|_| invalid())?,
+            opening: flags.opening,
+            no_guess: flags.no_guess,
+            rating: flags.rating,
+            layout: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_code_round_trip() {
+        let code: ShareCode = "16x30-99-7.12-2a5f-on2".parse().unwrap();
+        assert_eq!(code.config.size.rows, 16);
+        assert_eq!(code.config.size.cols, 30);
+        assert_eq!(code.config.mines, 99);
+        assert_eq!(code.start, GridLoc { row: 7, col: 12 });
+        assert_eq!(code.seed, 0x2a5f);
+        assert!(code.opening && code.no_guess);
+        assert_eq!(code.rating, Some(Rating::Hard));
+        assert_eq!(code.to_string(), "16x30-99-7.12-2a5f-on2");
+
+        let hex: ShareCode = "10x9-10-0.0-1-hto".parse().unwrap();
+        assert_eq!(hex.config.topology, Topology::Hex);
+        assert!(hex.config.wrap);
+        assert_eq!(hex.to_string(), "10x9-10-0.0-1-hto");
+
+        let knight: ShareCode = "9x9-10-0.0-1-k".parse().unwrap();
+        assert_eq!(knight.config.neighborhood, Neighborhood::Knight);
+        assert_eq!(knight.to_string(), "9x9-10-0.0-1-k");
+
+        let stacked: ShareCode = "9x9-150-0.0-1-m3o2".parse().unwrap();
+        assert_eq!(stacked.config.mines_per_cell, 3);
+        assert_eq!(stacked.rating, Some(Rating::Hard));
+        assert_eq!(stacked.to_string(), "9x9-150-0.0-1-m3o2");
+
+        let plain: ShareCode = "9x9-10-0.0-1".parse().unwrap();
+        assert!(!plain.opening && !plain.no_guess && plain.rating.is_none());
+        assert_eq!(plain.to_string(), "9x9-10-0.0-1");
+
+        let crafted: ShareCode = "9x9:o.*.8*.69".parse().unwrap();
+        assert_eq!(crafted.config.mines, 2);
+        assert_eq!(crafted.start, GridLoc { row: 0, col: 0 });
+        assert_eq!(crafted.to_string(), "9x9:o.*.8*.69");
+        let stacked: ShareCode = "10x10-htm2:b.99".parse().unwrap();
+        assert_eq!(stacked.config.mines, 2);
+        assert_eq!(stacked.to_string(), "10x10-htm2:b.99");
+    }
+
+    #[test]
+    fn test_share_code_rejects_invalid() {
+        for code in [
+            "",
+            "9x9-10",
+            "9x9-10-9.0-1",
+            "9x9-81-0.0-1",
+            "9x9-10-0.0-zz",
+            "9x9-10-0.0-1-x",
+            "9x9-10-0.0-1-4",
+            "9x9-10-0.0-1-12",
+            "9x9-10-0.0-1-ht",
+            "9x9-10-0.0-1-kw",
+            "9x9-10-0.0-1-hk",
+            "9x9-10-0.0-1-m",
+            "9x9-10-0.0-1-m0",
+            "9x9-10-0.0-1-m9",
+            "9x9:.81",
+            "9x9:o.*.8*.68",
+            "9x9-o:o.*.8*.69",
+            "9x9:o.b.8*.69",
+            "4294967296x4294967296-1-0.0-1",
+            "100000x100000:.",
+            "5x5:o.18446744073709551615",
+            "5x5:o.99999999999999999999999",
+        ] {
+            assert!(code.parse::<ShareCode>().is_err(), "{code}");
+        }
+    }
+
+    #[test]
+    fn test_share_code_generates_same_board() {
+        let code: ShareCode = "9x9-10-4.4-6767-o".parse().unwrap();
+        let (first, second) = (code.generate().unwrap(), code.generate().unwrap());
+        assert!(first.locs().all(|loc| {
+            first.get(loc.row, loc.col).unwrap().mines
+                == second.get(loc.row, loc.col).unwrap().mines
+        }));
+    }
+}
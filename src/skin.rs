@@ -0,0 +1,187 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use iced::{Color, widget::image};
+use serde::Deserialize;
+
+use crate::{
+    assets::{BUILTIN_TILES, TILE_NAMES},
+    storage,
+};
+
+/// Name shown for the skin compiled into the game.
+pub const BUILTIN: &str = "Built-in";
+
+/// File in a skin directory overriding the colors around the board.
+const COLORS_FILE: &str = "colors.json";
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Colors a skin sets around the board. Colors it leaves out come from the theme.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SkinColors {
+    pub background: Option<Color>,
+    pub board: Option<Color>,
+    pub text: Option<Color>,
+}
+
+/// `colors.json` as written by skin authors, with colors as hex strings like `"#4a4a4a"`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ColorsFile {
+    background: Option<String>,
+    board: Option<String>,
+    text: Option<String>,
+}
+
+/// The sprites and colors the game is drawn with.
+#[derive(Debug)]
+pub struct Skin {
+    pub name: String,
+    /// One handle per tile, in the order of `TILE_NAMES`.
+    pub tiles: Vec<image::Handle>,
+    pub colors: SkinColors,
+}
+
+impl Skin {
+    /// Returns the skin compiled into the game.
+    pub fn builtin() -> Self {
+        Self {
+            name: BUILTIN.to_string(),
+            tiles: BUILTIN_TILES
+                .iter()
+                .map(|bytes| image::Handle::from_bytes(*bytes))
+                .collect(),
+            colors: SkinColors::default(),
+        }
+    }
+
+    /// Loads a skin from a directory holding one PNG per name in `TILE_NAMES`
+    /// and optionally a `colors.json`.
+    ///
+    /// Fails if any tile is missing or is not a PNG, or if the colors cannot be read.
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let mut tiles = Vec::with_capacity(TILE_NAMES.len());
+        let mut missing = Vec::new();
+        for name in TILE_NAMES {
+            match fs::read(dir.join(name)) {
+                Ok(bytes) if bytes.starts_with(PNG_SIGNATURE) => {
+                    tiles.push(image::Handle::from_bytes(bytes));
+                }
+                Ok(..) => return Err(format!("{name} is not a PNG image")),
+                Err(..) => missing.push(name),
+            }
+        }
+        if !missing.is_empty() {
+            return Err(format!("missing tiles: {}", missing.join(", ")));
+        }
+
+        let colors = match fs::read_to_string(dir.join(COLORS_FILE)) {
+            Ok(json) => serde_json::from_str::<ColorsFile>(&json)
+                .map_err(|error| format!("invalid {COLORS_FILE}: {error}"))?
+                .parse()?,
+            Err(..) => SkinColors::default(),
+        };
+        let name = dir.file_name().map_or_else(
+            || dir.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        Ok(Self {
+            name,
+            tiles,
+            colors,
+        })
+    }
+}
+
+impl ColorsFile {
+    fn parse(self) -> Result<SkinColors, String> {
+        let parse = |color: Option<String>| {
+            color
+                .map(|color| {
+                    color.parse::<Color>().map_err(|error| {
+                        format!("invalid color {color:?} in {COLORS_FILE}: {error}")
+                    })
+                })
+                .transpose()
+        };
+        Ok(SkinColors {
+            background: parse(self.background)?,
+            board: parse(self.board)?,
+            text: parse(self.text)?,
+        })
+    }
+}
+
+/// Returns the directory skins are installed in, one subdirectory per skin.
+pub fn skins_dir() -> Option<PathBuf> {
+    storage::data_dir().map(|dir| dir.join("skins"))
+}
+
+/// Returns the names of the installed skins, starting with the built-in one.
+pub fn available() -> Vec<String> {
+    let mut names: Vec<String> = skins_dir()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names.insert(0, BUILTIN.to_string());
+    names
+}
+
+/// Loads the installed skin called `name`.
+///
+/// If it cannot be loaded, the built-in skin is returned along with the reason.
+pub fn select(name: &str) -> (Skin, Option<String>) {
+    let skin = if name == BUILTIN {
+        Ok(Skin::builtin())
+    } else {
+        skins_dir()
+            .ok_or_else(|| "no home directory to load skins from".to_string())
+            .and_then(|dir| Skin::load(&dir.join(name)))
+    };
+    match skin {
+        Ok(skin) => (skin, None),
+        Err(error) => (Skin::builtin(), Some(format!("skin {name}: {error}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skin_validation() {
+        let dir = std::env::temp_dir().join(format!("coop-sweeper-skin-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (name, bytes) in TILE_NAMES.iter().zip(BUILTIN_TILES).skip(2) {
+            fs::write(dir.join(name), bytes).unwrap();
+        }
+        let error = Skin::load(&dir).unwrap_err();
+        assert_eq!(error, "missing tiles: 0.png, 1.png");
+
+        for (name, bytes) in TILE_NAMES.iter().zip(BUILTIN_TILES).take(2) {
+            fs::write(dir.join(name), bytes).unwrap();
+        }
+        fs::write(dir.join(COLORS_FILE), r##"{"background": "#102030"}"##).unwrap();
+        let skin = Skin::load(&dir).unwrap();
+        assert_eq!(skin.tiles.len(), TILE_NAMES.len());
+        assert_eq!(
+            skin.colors.background,
+            Some(Color::from_rgb8(0x10, 0x20, 0x30))
+        );
+        assert_eq!(skin.colors.text, None);
+
+        fs::write(dir.join(COLORS_FILE), r#"{"background": "grey"}"#).unwrap();
+        assert!(Skin::load(&dir).is_err());
+        fs::write(dir.join("flag.png"), "not an image").unwrap();
+        assert_eq!(Skin::load(&dir).unwrap_err(), "flag.png is not a PNG image");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
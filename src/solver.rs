@@ -0,0 +1,314 @@
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use crate::cell::CellType;
+use crate::grid::{CellRevealResult, Grid, GridConfig, GridLoc, SafeZone};
+
+/// How many boards `generate_matching` tries before giving up.
+const GENERATION_ATTEMPTS: usize = 2_000;
+
+/// How much reasoning a deduction required, ordered from easiest to hardest.
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub enum DeductionLevel {
+    /// A single number accounts for all of its hidden neighbors.
+    #[default]
+    Trivial,
+    /// Two numbers whose hidden neighbors overlap, one containing the other.
+    Subset,
+}
+
+/// A conclusion drawn about a hidden cell from the visible board.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Deduction {
+    Safe(GridLoc),
+    Mine(GridLoc),
+}
+
+/// A batch of deductions found together, along with the reasoning they needed.
+#[derive(Clone, Debug)]
+pub struct Step {
+    pub deductions: Vec<Deduction>,
+    pub level: DeductionLevel,
+}
+
+/// A number on the board: `cells` hold exactly `mines` mines between them.
+struct Constraint {
+    cells: HashSet<GridLoc>,
+    mines: usize,
+}
+
+/// Builds one constraint per revealed number that still borders hidden cells.
+///
+/// Flags are trusted as mines, just like the player would.
+fn constraints(grid: &Grid) -> Vec<Constraint> {
+    grid.locs()
+        .filter(|loc| {
+            let cell = grid.get(loc.row, loc.col).unwrap();
+            matches!(cell.cell_type, CellType::Revealed) && !cell.is_mine()
+        })
+        .filter_map(|loc| {
+            let cells: HashSet<GridLoc> = grid
+                .neighbors(loc)
+                .filter(|n| matches!(grid.get(n.row, n.col).unwrap().cell_type, CellType::Hidden))
+                .collect();
+            let mines = grid
+                .count_neighboring_mines(loc)
+                .checked_sub(grid.count_neighboring_flags(loc))?;
+            (!cells.is_empty()).then_some(Constraint {
+                cells,
+                mines: mines as usize,
+            })
+        })
+        .collect()
+}
+
+/// Sorts deductions into a stable order and drops duplicates.
+fn finish(mut deductions: Vec<Deduction>, level: DeductionLevel) -> Option<Step> {
+    if deductions.is_empty() {
+        return None;
+    }
+    let loc = |deduction: &Deduction| match *deduction {
+        Deduction::Safe(loc) | Deduction::Mine(loc) => loc,
+    };
+    deductions.sort_by_key(loc);
+    deductions.dedup();
+    Some(Step { deductions, level })
+}
+
+/// Finds every cell that can be proven safe or a mine, preferring single-number reasoning.
+///
+/// Returns `None` when no deduction can be made and the player would have to guess.
+///
+/// On multi-mine boards a cell is only proven a mine once it must hold as many
+/// mines as a cell can, so it can be flagged completely.
+pub fn deduce(grid: &Grid) -> Option<Step> {
+    let constraints = constraints(grid);
+    let full = grid.mines_per_cell() as usize;
+
+    let mut deductions = Vec::new();
+    for constraint in &constraints {
+        if constraint.mines == 0 {
+            deductions.extend(constraint.cells.iter().copied().map(Deduction::Safe));
+        } else if constraint.mines == constraint.cells.len() * full {
+            deductions.extend(constraint.cells.iter().copied().map(Deduction::Mine));
+        }
+    }
+    if let Some(step) = finish(deductions, DeductionLevel::Trivial) {
+        return Some(step);
+    }
+
+    let mut deductions = Vec::new();
+    for small in &constraints {
+        for large in &constraints {
+            if small.cells.len() >= large.cells.len() || !small.cells.is_subset(&large.cells) {
+                continue;
+            }
+            let Some(mines) = large.mines.checked_sub(small.mines) else {
+                continue;
+            };
+            let rest = large.cells.difference(&small.cells).copied();
+            if mines == 0 {
+                deductions.extend(rest.map(Deduction::Safe));
+            } else if mines == (large.cells.len() - small.cells.len()) * full {
+                deductions.extend(rest.map(Deduction::Mine));
+            }
+        }
+    }
+    finish(deductions, DeductionLevel::Subset)
+}
+
+/// Picks the hidden cell least likely to be a mine.
+///
+/// Cells next to numbers use the worst local ratio of remaining mines to room
+/// for mines among hidden neighbors; all other cells use the density of the
+/// mines left unaccounted for.
+pub fn best_guess(grid: &Grid) -> Option<GridLoc> {
+    let constraints = constraints(grid);
+    let full = grid.mines_per_cell() as f32;
+    let hidden: Vec<GridLoc> = grid
+        .locs()
+        .filter(|loc| {
+            matches!(
+                grid.get(loc.row, loc.col).unwrap().cell_type,
+                CellType::Hidden
+            )
+        })
+        .collect();
+    let mines_left = grid.count_mines().saturating_sub(grid.count_flags());
+    let density = mines_left as f32 / (hidden.len().max(1) as f32 * full);
+
+    hidden
+        .into_iter()
+        .map(|loc| {
+            let probability = constraints
+                .iter()
+                .filter(|constraint| constraint.cells.contains(&loc))
+                .map(|constraint| constraint.mines as f32 / (constraint.cells.len() as f32 * full))
+                .reduce(f32::max)
+                .unwrap_or(density);
+            (loc, probability)
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(loc, _)| loc)
+}
+
+/// Returns a single move to play next, as a player would one click at a time.
+///
+/// Falls back to `best_guess` when nothing can be deduced and `guess` is set;
+/// guesses are returned as `Deduction::Safe` since they are meant to be revealed.
+pub fn next_move(grid: &Grid, guess: bool) -> Option<Deduction> {
+    match deduce(grid) {
+        Some(step) => step.deductions.into_iter().next(),
+        None if guess => best_guess(grid).map(Deduction::Safe),
+        None => None,
+    }
+}
+
+/// Applies a deduction to the grid, revealing safe cells and flagging mines.
+pub fn apply(grid: &mut Grid, deduction: Deduction) -> CellRevealResult {
+    match deduction {
+        Deduction::Safe(loc) => grid.cascade_reveal(loc),
+        Deduction::Mine(loc) => {
+            if matches!(
+                grid.get(loc.row, loc.col).unwrap().cell_type,
+                CellType::Hidden
+            ) {
+                grid.set_flags(loc, grid.mines_per_cell());
+            }
+            CellRevealResult::Success
+        }
+    }
+}
+
+/// Summary of a solver run over a board.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SolveReport {
+    /// `true` if every safe cell was revealed without hitting a mine.
+    pub solved: bool,
+    /// Number of times the solver got stuck and had to guess.
+    pub guesses: usize,
+    /// Hardest reasoning the solver needed along the way.
+    pub hardest: DeductionLevel,
+}
+
+/// Plays a populated grid to the end.
+///
+/// Without `guess`, the solver stops as soon as no deduction is possible. With it,
+/// the solver falls back to `best_guess` and keeps going until it wins or hits a mine.
+pub fn solve(grid: &mut Grid, guess: bool) -> SolveReport {
+    let mut report = SolveReport::default();
+    while !grid.all_safe_revealed() {
+        if let Some(step) = deduce(grid) {
+            report.hardest = report.hardest.max(step.level);
+            for deduction in step.deductions {
+                apply(grid, deduction);
+            }
+            continue;
+        }
+        let Some(loc) = best_guess(grid).filter(|_| guess) else {
+            return report;
+        };
+        report.guesses += 1;
+        if matches!(grid.cascade_reveal(loc), CellRevealResult::Mine) {
+            return report;
+        }
+    }
+    report.solved = true;
+    report
+}
+
+/// Returns `true` if the grid can be cleared from its current state without guessing.
+pub fn is_solvable(grid: &Grid) -> bool {
+    solve(&mut grid.clone(), false).solved
+}
+
+/// Generates boards from `rng` until one satisfies `accept`.
+///
+/// Returns `None` if none is found within a fixed number of attempts.
+pub fn generate_matching<R: Rng>(
+    config: &GridConfig,
+    loc: GridLoc,
+    safe_zone: &SafeZone,
+    rng: &mut R,
+    accept: impl Fn(&Grid) -> bool,
+) -> Option<Grid> {
+    (0..GENERATION_ATTEMPTS).find_map(|_| {
+        let mut grid = config.new_grid();
+        grid.populate_mines_with_rng(loc, config.mines, safe_zone, rng);
+        accept(&grid).then_some(grid)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        grid::GridSize,
+        topology::{Neighborhood, Topology},
+    };
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn grid_with_mines(size: GridSize, mines: &[GridLoc]) -> Grid {
+        let mut grid = Grid::new(size, Topology::Square);
+        for mine in mines {
+            grid.get_mut(mine.row, mine.col).unwrap().mines = 1;
+        }
+        grid
+    }
+
+    #[test]
+    fn test_deduce_trivial() {
+        // The 1 in the corner has a single hidden neighbor left after the cascade.
+        let mut grid =
+            grid_with_mines(GridSize { rows: 3, cols: 3 }, &[GridLoc { row: 2, col: 2 }]);
+        grid.cascade_reveal(GridLoc { row: 0, col: 0 });
+        let step = deduce(&grid).unwrap();
+        assert_eq!(
+            step.deductions,
+            vec![Deduction::Mine(GridLoc { row: 2, col: 2 })]
+        );
+        assert_eq!(step.level, DeductionLevel::Trivial);
+    }
+
+    #[test]
+    fn test_solve_without_guessing() {
+        let mut grid =
+            grid_with_mines(GridSize { rows: 3, cols: 3 }, &[GridLoc { row: 2, col: 2 }]);
+        grid.cascade_reveal(GridLoc { row: 0, col: 0 });
+        let report = solve(&mut grid, false);
+        assert!(report.solved);
+        assert_eq!(report.guesses, 0);
+    }
+
+    #[test]
+    fn test_best_guess_avoids_likely_mines() {
+        // Revealing the corner leaves a 1 whose three hidden neighbors hold its mine,
+        // while the far side of the board is mostly safe.
+        let mut grid =
+            grid_with_mines(GridSize { rows: 5, cols: 5 }, &[GridLoc { row: 1, col: 1 }]);
+        grid.cascade_reveal(GridLoc { row: 0, col: 0 });
+        let guess = best_guess(&grid).unwrap();
+        assert!(guess.row > 1 || guess.col > 1);
+    }
+
+    #[test]
+    fn test_generate_no_guess() {
+        let mut rng = ChaCha20Rng::seed_from_u64(6767);
+        let loc = GridLoc { row: 4, col: 4 };
+        let config = GridConfig {
+            size: GridSize { rows: 9, cols: 9 },
+            mines: 10,
+            topology: Topology::Square,
+            wrap: false,
+            neighborhood: Neighborhood::Standard,
+            mines_per_cell: 1,
+        };
+        let grid =
+            generate_matching(&config, loc, &SafeZone::Opening, &mut rng, is_solvable).unwrap();
+        assert_eq!(grid.count_mines(), 10);
+        assert!(is_solvable(&grid));
+        assert!(generate_matching(&config, loc, &SafeZone::Opening, &mut rng, |_| false).is_none());
+    }
+}
@@ -3,6 +3,7 @@ use iced::{Element, widget::button};
 use crate::{
     grid::{Grid, GridConfig, GridSize},
     message::Message,
+    topology::{Neighborhood, Topology},
 };
 
 #[derive(Debug)]
@@ -14,6 +15,23 @@ pub enum GameState {
     Lost(Grid),
 }
 
+impl GameState {
+    /// Decides whether the game goes on, or is won or lost, after a move on `grid`.
+    ///
+    /// Finished boards are fully revealed.
+    pub fn resolve(mut grid: Grid, hit_mine: bool) -> Self {
+        if hit_mine {
+            grid.reveal_all();
+            Self::Lost(grid)
+        } else if grid.is_won() {
+            grid.reveal_all();
+            Self::Won(grid)
+        } else {
+            Self::Started(grid)
+        }
+    }
+}
+
 impl Default for GameState {
     fn default() -> Self {
         Self::Initialized(Difficulty::default().state.clone())
@@ -32,6 +50,10 @@ impl Difficulty {
         state: &GridConfig {
             size: GridSize { rows: 9, cols: 9 },
             mines: 10,
+            topology: Topology::Square,
+            wrap: false,
+            neighborhood: Neighborhood::Standard,
+            mines_per_cell: 1,
         },
     };
     pub const DIFF_MEDIUM: Self = Self {
@@ -39,6 +61,10 @@ impl Difficulty {
         state: &GridConfig {
             size: GridSize { rows: 16, cols: 16 },
             mines: 40,
+            topology: Topology::Square,
+            wrap: false,
+            neighborhood: Neighborhood::Standard,
+            mines_per_cell: 1,
         },
     };
     pub const DIFF_HARD: Self = Self {
@@ -46,6 +72,10 @@ impl Difficulty {
         state: &GridConfig {
             size: GridSize { rows: 16, cols: 30 },
             mines: 99,
+            topology: Topology::Square,
+            wrap: false,
+            neighborhood: Neighborhood::Standard,
+            mines_per_cell: 1,
         },
     };
     pub const DIFF_EXTREME: Self = Self {
@@ -53,6 +83,10 @@ impl Difficulty {
         state: &GridConfig {
             size: GridSize { rows: 30, cols: 24 },
             mines: 160,
+            topology: Topology::Square,
+            wrap: false,
+            neighborhood: Neighborhood::Standard,
+            mines_per_cell: 1,
         },
     };
     pub const DIFF_ALL: &[Self] = &[
@@ -62,6 +96,13 @@ impl Difficulty {
         Self::DIFF_EXTREME,
     ];
 
+    /// Returns the preset called `name`, ignoring case.
+    pub fn named(name: &str) -> Option<&'static Self> {
+        Self::DIFF_ALL
+            .iter()
+            .find(|difficulty| difficulty.name.eq_ignore_ascii_case(name))
+    }
+
     pub fn display(&self) -> Element<'static, Message> {
         button(self.name)
             .on_press(Message::InputGridConfig(self.state.clone()))
@@ -0,0 +1,62 @@
+use std::{fs, io, path::PathBuf};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+/// Returns the directory where the game keeps its files between runs.
+///
+/// Follows `XDG_DATA_HOME` on Linux, `APPDATA` on Windows and falls back to
+/// `~/.local/share`, or `None` if no home directory can be found.
+pub fn data_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .or_else(|| std::env::var_os("APPDATA"))
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+        })?;
+    Some(base.join("coop-sweeper"))
+}
+
+/// Returns the directory where the game keeps the player's settings.
+///
+/// Follows `XDG_CONFIG_HOME` on Linux, `APPDATA` on Windows and falls back to
+/// `~/.config`, or `None` if no home directory can be found.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .or_else(|| std::env::var_os("APPDATA"))
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("coop-sweeper"))
+}
+
+/// Reads a JSON file from the data directory, falling back to the default if
+/// it is missing or unreadable.
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    read(data_dir(), name)
+}
+
+/// Writes a value as JSON into the data directory, creating it if needed.
+pub fn save<T: Serialize>(name: &str, value: &T) -> io::Result<()> {
+    write(data_dir(), name, value)
+}
+
+/// Reads a JSON file from the config directory, like `load`.
+pub fn load_config<T: DeserializeOwned + Default>(name: &str) -> T {
+    read(config_dir(), name)
+}
+
+/// Writes a value as JSON into the config directory, like `save`.
+pub fn save_config<T: Serialize>(name: &str, value: &T) -> io::Result<()> {
+    write(config_dir(), name, value)
+}
+
+fn read<T: DeserializeOwned + Default>(dir: Option<PathBuf>, name: &str) -> T {
+    dir.and_then(|dir| fs::read_to_string(dir.join(name)).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn write<T: Serialize>(dir: Option<PathBuf>, name: &str, value: &T) -> io::Result<()> {
+    let dir = dir.ok_or_else(|| io::Error::other("no home directory to save into"))?;
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(name), serde_json::to_string_pretty(value)?)
+}
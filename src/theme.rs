@@ -1,16 +1,115 @@
-use iced::{Background, Color, color};
-
-pub const PRIMARY_COLOR: Color = color!(74, 74, 74);
-pub const BACKGROUND_COLOR: Background = Background::Color(PRIMARY_COLOR);
-pub const TEXT_COLOR: Color = color!(200, 200, 200);
-pub const GRID_CONTAINER_BACKGROUND_COLOR: Background = Background::Color(color!(48, 48, 48));
-// pub const BORDER: Border = Border {
-//     color: Color::from_rgb8(49, 49, 49),
-//     ..Default::default()
-// };
-
-// pub fn theme(_app: &App) -> Option<Theme> {
-//     Some(Theme::custom("Theme", {
-//         ..Palette::CATPPUCCIN_FRAPPE.extend(EXTENDED_CATPPUCCIN_LATTE)
-//     }))
-// }
+use std::fmt;
+
+use iced::{Background, Color, Theme, color, theme::Palette};
+use serde::{Deserialize, Serialize};
+
+use crate::skin::SkinColors;
+
+/// Outline of boards whose edges wrap around.
+pub const WRAP_BORDER_COLOR: Color = color!(86, 182, 194);
+/// Colors of the players in multiplayer games, by player number.
+const PLAYER_COLORS: [Color; 6] = [
+    color!(229, 192, 123),
+    color!(97, 175, 239),
+    color!(152, 195, 121),
+    color!(224, 108, 117),
+    color!(198, 120, 221),
+    color!(209, 154, 102),
+];
+
+const DARK: Palette = Palette {
+    background: color!(74, 74, 74),
+    text: color!(200, 200, 200),
+    primary: color!(97, 175, 239),
+    success: color!(152, 195, 121),
+    warning: color!(229, 192, 123),
+    danger: color!(224, 108, 117),
+};
+
+const LIGHT: Palette = Palette {
+    background: color!(236, 236, 236),
+    text: color!(32, 32, 32),
+    primary: color!(37, 99, 235),
+    success: color!(22, 135, 62),
+    warning: color!(180, 120, 0),
+    danger: color!(200, 38, 38),
+};
+
+const HIGH_CONTRAST: Palette = Palette {
+    background: Color::BLACK,
+    text: Color::WHITE,
+    primary: color!(255, 221, 0),
+    success: color!(0, 230, 118),
+    warning: color!(255, 145, 0),
+    danger: color!(255, 82, 82),
+};
+
+/// Colors of the window and its widgets, chosen by the player.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorScheme {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+impl ColorScheme {
+    pub const ALL: [ColorScheme; 3] = [
+        ColorScheme::Dark,
+        ColorScheme::Light,
+        ColorScheme::HighContrast,
+    ];
+
+    pub fn palette(self) -> Palette {
+        match self {
+            ColorScheme::Dark => DARK,
+            ColorScheme::Light => LIGHT,
+            ColorScheme::HighContrast => HIGH_CONTRAST,
+        }
+    }
+
+    /// Returns the theme styling every widget in this color scheme.
+    pub fn theme(self) -> Theme {
+        Theme::custom(self.to_string(), self.palette())
+    }
+
+    /// Returns the color behind the board, which frames the cell sprites.
+    fn board(self) -> Color {
+        match self {
+            ColorScheme::Dark => color!(48, 48, 48),
+            ColorScheme::Light => color!(192, 192, 192),
+            ColorScheme::HighContrast => color!(40, 40, 40),
+        }
+    }
+}
+
+impl fmt::Display for ColorScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ColorScheme::Dark => "Dark",
+            ColorScheme::Light => "Light",
+            ColorScheme::HighContrast => "High contrast",
+        })
+    }
+}
+
+/// Returns the background of the window, which skins can override.
+pub fn background(scheme: ColorScheme, colors: SkinColors) -> Background {
+    Background::Color(colors.background.unwrap_or(scheme.palette().background))
+}
+
+/// Returns the background behind the board, which skins can override.
+pub fn board_background(scheme: ColorScheme, colors: SkinColors) -> Background {
+    Background::Color(colors.board.unwrap_or(scheme.board()))
+}
+
+/// Returns the color of text outside the board, which skins can override.
+pub fn text_color(scheme: ColorScheme, colors: SkinColors) -> Color {
+    colors.text.unwrap_or(scheme.palette().text)
+}
+
+/// Returns the color of a player in a multiplayer game.
+pub fn player_color(player: u8) -> Color {
+    PLAYER_COLORS[player as usize % PLAYER_COLORS.len()]
+}
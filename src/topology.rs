@@ -0,0 +1,288 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::grid::{GridLoc, GridSize};
+
+/// Row and column offsets of the 8 cells around a square cell, in row-major order.
+const SQUARE_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Offsets of the 6 cells around a hexagon in an even row.
+///
+/// Odd rows are drawn shifted right by half a cell, so the rows above and below
+/// an even row touch it on the left.
+const HEX_EVEN_OFFSETS: [(isize, isize); 6] = [(-1, -1), (-1, 0), (0, -1), (0, 1), (1, -1), (1, 0)];
+
+/// Offsets of the 6 cells around a hexagon in an odd row.
+const HEX_ODD_OFFSETS: [(isize, isize); 6] = [(-1, 0), (-1, 1), (0, -1), (0, 1), (1, 0), (1, 1)];
+
+/// Offsets of the 4 cells sharing an edge with a square cell.
+const ORTHOGONAL_OFFSETS: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// Offsets of the 8 cells a chess knight can jump to.
+const KNIGHT_OFFSETS: [(isize, isize); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+/// Offsets of the 24 other cells in the 5x5 square centered on a cell.
+const WIDE_OFFSETS: [(isize, isize); 24] = {
+    let mut offsets = [(0, 0); 24];
+    let mut index = 0;
+    let mut row = -2;
+    while row <= 2 {
+        let mut col = -2;
+        while col <= 2 {
+            if row != 0 || col != 0 {
+                offsets[index] = (row, col);
+                index += 1;
+            }
+            col += 1;
+        }
+        row += 1;
+    }
+    offsets
+};
+
+/// The shape of the cells, which decides which cells are neighbors.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Topology {
+    /// Classic square cells with 8 neighbors.
+    #[default]
+    Square,
+    /// Hexagonal cells with 6 neighbors, laid out in rows with every odd row
+    /// shifted right by half a cell.
+    Hex,
+}
+
+/// Which cells around a square cell its number counts.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Neighborhood {
+    /// The cells touching it, as defined by the topology.
+    #[default]
+    Standard,
+    /// Only the 4 cells sharing an edge with it.
+    Orthogonal,
+    /// The 8 cells a chess knight could jump to.
+    Knight,
+    /// All 24 other cells of the 5x5 square around it.
+    Wide,
+}
+
+impl Neighborhood {
+    pub const ALL: [Self; 4] = [Self::Standard, Self::Orthogonal, Self::Knight, Self::Wide];
+}
+
+impl fmt::Display for Neighborhood {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Standard => "Standard",
+            Self::Orthogonal => "Orthogonal",
+            Self::Knight => "Knight",
+            Self::Wide => "Wide",
+        })
+    }
+}
+
+impl FromStr for Neighborhood {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|neighborhood| neighborhood.to_string().eq_ignore_ascii_case(name))
+            .ok_or_else(|| "expected one of Standard, Orthogonal, Knight, Wide".to_string())
+    }
+}
+
+impl Topology {
+    pub const ALL: [Self; 2] = [Self::Square, Self::Hex];
+
+    /// Returns the offsets of the neighbors of a cell in `row`.
+    ///
+    /// Hexagonal cells only support the standard neighborhood and ignore `neighborhood`.
+    fn offsets(self, neighborhood: Neighborhood, row: usize) -> &'static [(isize, isize)] {
+        match (self, neighborhood) {
+            (Self::Square, Neighborhood::Standard) => &SQUARE_OFFSETS,
+            (Self::Square, Neighborhood::Orthogonal) => &ORTHOGONAL_OFFSETS,
+            (Self::Square, Neighborhood::Knight) => &KNIGHT_OFFSETS,
+            (Self::Square, Neighborhood::Wide) => &WIDE_OFFSETS,
+            (Self::Hex, _) if row.is_multiple_of(2) => &HEX_EVEN_OFFSETS,
+            (Self::Hex, _) => &HEX_ODD_OFFSETS,
+        }
+    }
+
+    /// Returns how many neighbors a cell away from the edges has.
+    pub fn neighbor_count(self, neighborhood: Neighborhood) -> usize {
+        self.offsets(neighborhood, 0).len()
+    }
+
+    /// Returns an iterator over the neighbors of `loc` on a board of `size`.
+    ///
+    /// Neighbors past an edge are dropped, unless the board `wrap`s around, in
+    /// which case they continue on the opposite edge.
+    pub fn neighbors(
+        self,
+        neighborhood: Neighborhood,
+        loc: GridLoc,
+        size: GridSize,
+        wrap: bool,
+    ) -> impl Iterator<Item = GridLoc> {
+        let step = move |start: usize, offset: isize, len: usize| {
+            if wrap {
+                Some((start + len).checked_add_signed(offset)? % len)
+            } else {
+                start
+                    .checked_add_signed(offset)
+                    .filter(|&index| index < len)
+            }
+        };
+        self.offsets(neighborhood, loc.row)
+            .iter()
+            .filter_map(move |&(rows, cols)| {
+                Some(GridLoc {
+                    row: step(loc.row, rows, size.rows)?,
+                    col: step(loc.col, cols, size.cols)?,
+                })
+            })
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Square => "Square",
+            Self::Hex => "Hex",
+        })
+    }
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|topology| topology.to_string().eq_ignore_ascii_case(name))
+            .ok_or_else(|| "expected one of Square, Hex".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const SIZE: GridSize = GridSize { rows: 5, cols: 5 };
+
+    #[test]
+    fn test_square_neighbors() {
+        let center = GridLoc { row: 2, col: 2 };
+        assert_eq!(
+            Topology::Square
+                .neighbors(Neighborhood::Standard, center, SIZE, false)
+                .count(),
+            8
+        );
+        let corner = GridLoc { row: 0, col: 0 };
+        assert_eq!(
+            Topology::Square
+                .neighbors(Neighborhood::Standard, corner, SIZE, false)
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_neighborhoods() {
+        let center = GridLoc { row: 2, col: 2 };
+        for (neighborhood, count) in [
+            (Neighborhood::Orthogonal, 4),
+            (Neighborhood::Knight, 8),
+            (Neighborhood::Wide, 24),
+        ] {
+            let neighbors: HashSet<_> = Topology::Square
+                .neighbors(neighborhood, center, SIZE, false)
+                .collect();
+            assert_eq!(neighbors.len(), count);
+            assert!(!neighbors.contains(&center));
+        }
+        let knight: Vec<_> = Topology::Square
+            .neighbors(
+                Neighborhood::Knight,
+                GridLoc { row: 0, col: 0 },
+                SIZE,
+                false,
+            )
+            .collect();
+        assert_eq!(
+            knight,
+            vec![GridLoc { row: 1, col: 2 }, GridLoc { row: 2, col: 1 }]
+        );
+    }
+
+    #[test]
+    fn test_wrapped_neighbors() {
+        let corner = GridLoc { row: 0, col: 0 };
+        let neighbors: HashSet<_> = Topology::Square
+            .neighbors(Neighborhood::Standard, corner, SIZE, true)
+            .collect();
+        assert_eq!(neighbors.len(), 8);
+        assert!(neighbors.contains(&GridLoc { row: 4, col: 4 }));
+
+        let size = GridSize { rows: 6, cols: 5 };
+        for row in 0..size.rows {
+            let loc = GridLoc { row, col: 0 };
+            let neighbors: HashSet<_> = Topology::Hex
+                .neighbors(Neighborhood::Standard, loc, size, true)
+                .collect();
+            assert_eq!(neighbors.len(), 6);
+            for neighbor in neighbors {
+                assert!(
+                    Topology::Hex
+                        .neighbors(Neighborhood::Standard, neighbor, size, true)
+                        .any(|n| n == loc)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_hex_neighbors_are_symmetric() {
+        for row in 0..SIZE.rows {
+            for col in 0..SIZE.cols {
+                let loc = GridLoc { row, col };
+                let neighbors: Vec<_> = Topology::Hex
+                    .neighbors(Neighborhood::Standard, loc, SIZE, false)
+                    .collect();
+                if (1..SIZE.rows - 1).contains(&row) && (1..SIZE.cols - 1).contains(&col) {
+                    assert_eq!(neighbors.len(), 6);
+                }
+                for neighbor in neighbors {
+                    assert!(
+                        Topology::Hex
+                            .neighbors(Neighborhood::Standard, neighbor, SIZE, false)
+                            .any(|n| n == loc)
+                    );
+                }
+            }
+        }
+    }
+}
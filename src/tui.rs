@@ -0,0 +1,413 @@
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Alignment, Constraint, Layout},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Paragraph},
+};
+
+use crate::{
+    cell::{Cell, CellType},
+    cli::{LaunchOptions, LaunchState},
+    grid::{CellChordResult, CellRevealResult, Grid, GridConfig, GridLoc, SafeZone},
+    rating::Rating,
+    share::ShareCode,
+    state::GameState,
+    topology::Topology,
+};
+
+/// How often the screen is redrawn while waiting for input, so the timer keeps ticking.
+const TICK: Duration = Duration::from_millis(250);
+
+/// Colors of the numbers 1 through 8, following the classic palette.
+const DIGIT_COLORS: [Color; 8] = [
+    Color::LightBlue,
+    Color::Green,
+    Color::LightRed,
+    Color::Blue,
+    Color::Red,
+    Color::Cyan,
+    Color::Magenta,
+    Color::Gray,
+];
+
+/// A full-screen terminal frontend driving the same game engine as the window.
+struct Tui {
+    state: GameState,
+    config: GridConfig,
+    cursor: GridLoc,
+    started: Option<Instant>,
+    /// Time the finished game took, frozen when it ended.
+    finished: Option<Duration>,
+    seed: Option<u64>,
+    no_guess: bool,
+    rating: Option<Rating>,
+    safe_zone: SafeZone,
+    share_code: Option<ShareCode>,
+    /// Why the last board could not be generated.
+    error: Option<String>,
+    quit: bool,
+}
+
+/// Runs the terminal frontend until the player quits.
+pub fn run(options: LaunchOptions) -> io::Result<()> {
+    let mut tui = Tui::new(options);
+    let mut terminal = ratatui::init();
+    let result = tui.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl Tui {
+    fn new(options: LaunchOptions) -> Self {
+        let config = match &options.state {
+            LaunchState::Config(config) => config.clone(),
+            LaunchState::Code(code) => code.config.clone(),
+        };
+        let mut tui = Self {
+            state: GameState::Initialized(config.clone()),
+            config,
+            cursor: GridLoc::default(),
+            started: None,
+            finished: None,
+            seed: options.seed,
+            no_guess: options.no_guess,
+            rating: options.rating,
+            safe_zone: SafeZone::default(),
+            share_code: None,
+            error: None,
+            quit: false,
+        };
+        if let LaunchState::Code(code) = options.state {
+            tui.cursor = code.start;
+            tui.start_game(code);
+        }
+        tui
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if event::poll(TICK)?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_key(key.code);
+            }
+        }
+        Ok(())
+    }
+
+    // ==================== Game Flow ====================
+
+    /// Resets to an empty board waiting for its first reveal.
+    fn new_game(&mut self, config: GridConfig) {
+        self.config = config.clone();
+        self.state = GameState::Initialized(config);
+        self.error = None;
+        self.started = None;
+        self.finished = None;
+        self.share_code = None;
+    }
+
+    /// Generates the board described by `code` and starts the clock.
+    fn start_game(&mut self, code: ShareCode) {
+        self.config = code.config.clone();
+        self.no_guess = code.no_guess;
+        self.rating = code.rating;
+        self.safe_zone = if code.opening {
+            SafeZone::Opening
+        } else {
+            SafeZone::Cell
+        };
+        match code.generate() {
+            Ok(grid) => {
+                self.error = None;
+                self.share_code = Some(code);
+                self.started = Some(Instant::now());
+                self.finish_move(grid, false);
+            }
+            Err(error) => {
+                self.error = Some(error);
+                self.state = GameState::Initialized(code.config);
+            }
+        }
+    }
+
+    fn finish_move(&mut self, grid: Grid, hit_mine: bool) {
+        self.state = GameState::resolve(grid, hit_mine);
+        if matches!(self.state, GameState::Won(..) | GameState::Lost(..)) {
+            self.finished = Some(self.elapsed());
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        match (self.finished, self.started) {
+            (Some(finished), _) => finished,
+            (None, Some(started)) => started.elapsed(),
+            (None, None) => Duration::ZERO,
+        }
+    }
+
+    /// Reveals the cell under the cursor, or chords it if it is already revealed.
+    fn reveal(&mut self) {
+        let loc = self.cursor;
+        match std::mem::take(&mut self.state) {
+            GameState::Initialized(config) => {
+                let code = ShareCode {
+                    config,
+                    start: loc,
+                    seed: self.seed.take().unwrap_or_else(rand::random),
+                    opening: matches!(self.safe_zone, SafeZone::Opening),
+                    no_guess: self.no_guess,
+                    rating: self.rating,
+                    layout: None,
+                };
+                self.start_game(code);
+            }
+            GameState::Started(mut grid) => {
+                let hit_mine = match grid.get(loc.row, loc.col).unwrap().cell_type {
+                    CellType::Revealed => {
+                        matches!(grid.chord_reveal(loc), CellChordResult::Mines(..))
+                    }
+                    _ => matches!(grid.cascade_reveal(loc), CellRevealResult::Mine),
+                };
+                self.finish_move(grid, hit_mine);
+            }
+            state => self.state = state,
+        }
+    }
+
+    fn flag(&mut self) {
+        match std::mem::take(&mut self.state) {
+            GameState::Started(mut grid) => {
+                grid.flag_cell(self.cursor);
+                self.finish_move(grid, false);
+            }
+            state => self.state = state,
+        }
+    }
+
+    /// Moves the cursor, stopping at the edges or wrapping around on wrapping boards.
+    fn move_cursor(&mut self, rows: isize, cols: isize) {
+        let size = self.config.size;
+        let step = |index: usize, offset: isize, len: usize| {
+            if self.config.wrap {
+                (index + len).saturating_add_signed(offset) % len
+            } else {
+                index.saturating_add_signed(offset).min(len - 1)
+            }
+        };
+        self.cursor.row = step(self.cursor.row, rows, size.rows);
+        self.cursor.col = step(self.cursor.col, cols, size.cols);
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Left | KeyCode::Char('h') => self.move_cursor(0, -1),
+            KeyCode::Right | KeyCode::Char('l') => self.move_cursor(0, 1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1, 0),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1, 0),
+            KeyCode::Char(' ') | KeyCode::Enter => self.reveal(),
+            KeyCode::Char('f') => self.flag(),
+            KeyCode::Char('n') => self.new_game(self.config.clone()),
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            _ => {}
+        }
+    }
+
+    // ==================== Rendering ====================
+
+    fn draw(&self, frame: &mut Frame) {
+        let [header, board, footer] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .areas(frame.area());
+
+        frame.render_widget(Paragraph::new(self.header()).centered(), header);
+        let mut board_widget = Paragraph::new(self.board()).centered();
+        if self.config.wrap {
+            board_widget = board_widget.block(
+                Block::bordered()
+                    .border_type(BorderType::LightDoubleDashed)
+                    .border_style(Style::new().cyan())
+                    .title(" edges wrap around ")
+                    .title_alignment(Alignment::Center),
+            );
+        }
+        frame.render_widget(board_widget, board);
+        frame.render_widget(Paragraph::new(self.footer()).centered(), footer);
+    }
+
+    fn header(&self) -> Line<'static> {
+        let (remaining, face) = match &self.state {
+            GameState::Started(grid) => (
+                grid.count_mines() as isize - grid.count_flags() as isize,
+                ":)",
+            ),
+            GameState::Won(..) => (0, "B)"),
+            GameState::Lost(grid) => (
+                grid.count_mines() as isize - grid.count_flags() as isize,
+                "X(",
+            ),
+            GameState::CreationScreen(config) | GameState::Initialized(config) => {
+                (config.mines as isize, ":)")
+            }
+        };
+        Line::from(vec![
+            Span::from(format!("Mines {remaining:03}")).red().bold(),
+            Span::from(format!("   {face}   ")).yellow().bold(),
+            Span::from(format!("Time {:03}", self.elapsed().as_secs().min(999)))
+                .red()
+                .bold(),
+        ])
+    }
+
+    fn board(&self) -> Vec<Line<'static>> {
+        let grid = match &self.state {
+            GameState::Started(grid) | GameState::Won(grid) | GameState::Lost(grid) => Some(grid),
+            GameState::CreationScreen(..) | GameState::Initialized(..) => None,
+        };
+        let size = self.config.size;
+        let width = Self::cell_width(&self.config);
+        (0..size.rows)
+            .map(|row| {
+                let spans = (0..size.cols).map(|col| {
+                    let loc = GridLoc { row, col };
+                    let span = match grid {
+                        Some(grid) => Self::cell(
+                            grid.get(row, col).unwrap(),
+                            grid.count_neighboring_mines(loc),
+                            width,
+                        ),
+                        None => Self::cell(&Cell::default(), 0, width),
+                    };
+                    if loc == self.cursor {
+                        span.add_modifier(Modifier::REVERSED)
+                    } else {
+                        span
+                    }
+                });
+                // Hexagonal boards shift odd rows right by half a cell, padding even
+                // rows on the other side so centering keeps the rows aligned.
+                let half = " ".repeat(width / 2);
+                let (before, after) = match self.config.topology {
+                    Topology::Hex if row % 2 == 1 => (half, String::new()),
+                    Topology::Hex => (String::new(), half),
+                    Topology::Square => (String::new(), String::new()),
+                };
+                Line::from_iter(
+                    std::iter::once(Span::from(before))
+                        .chain(spans)
+                        .chain(std::iter::once(Span::from(after))),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns how many columns every cell takes: the widest number or stack
+    /// the board can show, after a space separating it from the previous cell.
+    fn cell_width(config: &GridConfig) -> usize {
+        let digits = |count: usize| count.to_string().len();
+        let most = config.mines_per_cell as usize;
+        let number = digits(config.topology.neighbor_count(config.neighborhood) * most);
+        let stack = if most > 1 { 1 + digits(most) } else { 1 };
+        1 + number.max(stack)
+    }
+
+    fn cell(cell: &Cell, neighboring_mines: u8, width: usize) -> Span<'static> {
+        match cell.cell_type {
+            CellType::Hidden => Span::styled(format!("{:>width$}", "■"), Style::new().dark_gray()),
+            CellType::Flagged(flags) => Span::styled(
+                Self::stacked('F', flags, width),
+                Style::new().light_red().bold(),
+            ),
+            CellType::Revealed if cell.is_mine() => Span::styled(
+                Self::stacked('*', cell.mines, width),
+                Style::new().red().bold(),
+            ),
+            CellType::Revealed if neighboring_mines == 0 => {
+                Span::from(format!("{:>width$}", "·")).dark_gray()
+            }
+            CellType::Revealed => Span::styled(
+                format!("{neighboring_mines:>width$}"),
+                Style::new()
+                    .fg(DIGIT_COLORS
+                        .get(neighboring_mines as usize - 1)
+                        .copied()
+                        .unwrap_or(Color::White))
+                    .bold(),
+            ),
+        }
+    }
+
+    /// Draws a flag or mine symbol, followed by how many there are when more
+    /// than one, right-aligned in `width` columns.
+    fn stacked(symbol: char, count: u8, width: usize) -> String {
+        let stack = if count > 1 {
+            format!("{symbol}{count}")
+        } else {
+            symbol.to_string()
+        };
+        format!("{stack:>width$}")
+    }
+
+    fn footer(&self) -> Vec<Line<'static>> {
+        let status = match (&self.state, &self.share_code) {
+            (GameState::Won(..), Some(code)) => {
+                format!("You found all the mines! Board code: {code}")
+            }
+            (GameState::Lost(..), Some(code)) => format!("You hit a mine! Board code: {code}"),
+            (GameState::Initialized(..), _) => match &self.error {
+                Some(error) => format!("Could not start the game: {error}"),
+                None => String::new(),
+            },
+            _ => String::new(),
+        };
+        vec![
+            Line::from(status).bold(),
+            Line::from("←↓↑→/hjkl move · space reveal/chord · f flag · n new game · q quit")
+                .dark_gray(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{state::Difficulty, topology::Neighborhood};
+
+    #[test]
+    fn test_cell_width() {
+        let mut config = Difficulty::DIFF_EASY.state.clone();
+        assert_eq!(Tui::cell_width(&config), 2);
+        assert_eq!(Tui::stacked('F', 1, 2), " F");
+
+        // Knight moves reach 8 cells, so 5 mines each can add up to 40.
+        config.neighborhood = Neighborhood::Knight;
+        config.mines_per_cell = 5;
+        assert_eq!(Tui::cell_width(&config), 3);
+        assert_eq!(Tui::stacked('*', 5, 3), " *5");
+
+        // Every cell takes the same width, however many mines it counts.
+        config.neighborhood = Neighborhood::Wide;
+        let width = Tui::cell_width(&config);
+        assert_eq!(width, 4);
+        let cell = Cell {
+            cell_type: CellType::Revealed,
+            ..Cell::default()
+        };
+        for (neighboring_mines, expected) in [(7, "   7"), (120, " 120")] {
+            assert_eq!(Tui::cell(&cell, neighboring_mines, width).content, expected);
+        }
+        assert_eq!(Tui::stacked('F', 2, width).len(), width);
+    }
+}